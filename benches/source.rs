@@ -0,0 +1,139 @@
+//! Compare reading and memory mapping uncompressed FASTA input
+//!
+//! Run with `cargo bench --bench source`.  A FASTA file of `SOURCE_BENCH_SIZE` bytes (default
+//! 256 MiB) is generated in the temporary directory, unless `SOURCE_BENCH_FASTA` names an existing
+//! file to use instead, e.g. a multi-gigabyte reference genome.  `SOURCE_BENCH_FETCHES` sets the
+//! number of random regions fetched (default 100000).
+//!
+
+use rust_samtools::errors::Result;
+use rust_samtools::io::fai;
+use rust_samtools::io::source::{Access, Source};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const DEFAULT_SIZE: u64 = 256 << 20;
+const DEFAULT_FETCHES: usize = 100_000;
+const SEQUENCE_LEN: u64 = 16 << 20;
+const LINE_WIDTH: usize = 60;
+const FETCH_LEN: usize = 1000;
+const ACCESSES: [Access; 2] = [Access::Read, Access::Mmap];
+
+fn main() -> Result<()> {
+    let (path, generated) = match std::env::var_os("SOURCE_BENCH_FASTA") {
+        Some(path) => (PathBuf::from(path), false),
+        None => {
+            let path = std::env::temp_dir().join("rust-samtools-source-bench.fa");
+            generate(&path, env_or("SOURCE_BENCH_SIZE", DEFAULT_SIZE))?;
+            (path, true)
+        }
+    };
+    let fetches = env_or("SOURCE_BENCH_FETCHES", DEFAULT_FETCHES);
+    let size = std::fs::metadata(&path)?.len();
+    println!("input: {} ({} MiB)", path.display(), size >> 20);
+    let mut records = Vec::new();
+    for access in ACCESSES {
+        let (elapsed, indexed) = time(|| index(&path, access))?;
+        report("index", access, elapsed, size);
+        records = indexed;
+    }
+    for access in ACCESSES {
+        let (elapsed, bases) = time(|| fetch(&path, &records, access, fetches))?;
+        report("fetch", access, elapsed, bases);
+    }
+    if generated {
+        std::fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+/// Get a numeric environment variable, or `default` if it is not set
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Write a FASTA file of about `size` bytes with pseudo-random bases
+fn generate(path: &Path, size: u64) -> Result<()> {
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    let mut random = Random(1);
+    let mut written = 0;
+    let mut line = [b'\n'; LINE_WIDTH + 1];
+    let mut number = 0;
+    while written < size {
+        let header = format!(">seq{}\n", number);
+        writer.write_all(header.as_bytes())?;
+        written += header.len() as u64;
+        let mut remaining = SEQUENCE_LEN.min(size.saturating_sub(written)).max(1);
+        while remaining > 0 {
+            let len = remaining.min(LINE_WIDTH as u64) as usize;
+            for base in line[..len].iter_mut() {
+                *base = b"ACGT"[random.below(4)];
+            }
+            line[len] = b'\n';
+            writer.write_all(&line[..=len])?;
+            written += len as u64 + 1;
+            remaining -= len as u64;
+        }
+        number += 1;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Index the input, returning its records
+fn index(path: &Path, access: Access) -> Result<Vec<fai::Record>> {
+    let mut indexer = fai::Indexer::from_path_with(path, fai::IndexerFormat::FASTA, access)?;
+    let (records, _) = indexer.index(fai::Duplicates::default())?;
+    Ok(records)
+}
+
+/// Fetch random regions of the input, returning the number of bases read
+fn fetch(path: &Path, records: &[fai::Record], access: Access, fetches: usize) -> Result<u64> {
+    let mut reader =
+        fai::IndexedReader::new(Source::from_path_with(path, access)?, records.to_vec());
+    let mut random = Random(2);
+    let mut bases = 0;
+    for _ in 0..fetches {
+        let record = &records[random.below(records.len())];
+        let start = random.below(record.length.saturating_sub(FETCH_LEN) + 1);
+        let end = (start + FETCH_LEN).min(record.length);
+        bases += reader.fetch(&record.name, start, end)?.len() as u64;
+    }
+    Ok(bases)
+}
+
+/// Run `f`, returning its elapsed time and result
+fn time<T, F: FnOnce() -> Result<T>>(f: F) -> Result<(Duration, T)> {
+    let start = Instant::now();
+    let result = f()?;
+    Ok((start.elapsed(), result))
+}
+
+/// Print the elapsed time and throughput of a benchmark
+fn report(name: &str, access: Access, elapsed: Duration, bytes: u64) {
+    let seconds = elapsed.as_secs_f64();
+    println!(
+        "{:<6}{:<6}{:>10.3} s{:>10.1} MiB/s",
+        name,
+        format!("{:?}", access).to_lowercase(),
+        seconds,
+        bytes as f64 / (1 << 20) as f64 / seconds
+    );
+}
+
+/// Deterministic xorshift generator, so that both sources fetch the same regions
+struct Random(u64);
+
+impl Random {
+    /// Get a number in `0..n`
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
@@ -0,0 +1,96 @@
+use crate::errors::{Error, ErrorKind, Result};
+use crate::io::{dict, fai};
+
+pub const SUBCOMMAND: &str = "dict";
+const FILE_ARG: &str = "file";
+const OUTPUT_ARG: &str = "output";
+const OUTPUT_ARG_SHORT: char = 'o';
+const ASSEMBLY_ARG: &str = "assembly";
+const ASSEMBLY_ARG_SHORT: char = 'a';
+const SPECIES_ARG: &str = "species";
+const SPECIES_ARG_SHORT: char = 's';
+const URI_ARG: &str = "uri";
+const URI_ARG_SHORT: char = 'u';
+const NO_HEADER_FLAG: &str = "no-header";
+const NO_HEADER_FLAG_SHORT: char = 'H';
+const FILE_URI_PREFIX: &str = "file://";
+
+/// dict subcommand
+pub fn command() -> clap::Command<'static> {
+    clap::Command::new(SUBCOMMAND)
+        .about("Create a sequence dictionary")
+        .arg(clap::Arg::new(FILE_ARG).required(true))
+        .arg(
+            clap::Arg::new(OUTPUT_ARG)
+                .long(OUTPUT_ARG)
+                .short(OUTPUT_ARG_SHORT)
+                .takes_value(true)
+                .help("Output file [default: stdout]"),
+        )
+        .arg(
+            clap::Arg::new(ASSEMBLY_ARG)
+                .long(ASSEMBLY_ARG)
+                .short(ASSEMBLY_ARG_SHORT)
+                .takes_value(true)
+                .help("Assembly (AS tag)"),
+        )
+        .arg(
+            clap::Arg::new(SPECIES_ARG)
+                .long(SPECIES_ARG)
+                .short(SPECIES_ARG_SHORT)
+                .takes_value(true)
+                .help("Species (SP tag)"),
+        )
+        .arg(
+            clap::Arg::new(URI_ARG)
+                .long(URI_ARG)
+                .short(URI_ARG_SHORT)
+                .takes_value(true)
+                .help("URI (UR tag) [default: file://<absolute path of file>]"),
+        )
+        .arg(
+            clap::Arg::new(NO_HEADER_FLAG)
+                .long(NO_HEADER_FLAG)
+                .short(NO_HEADER_FLAG_SHORT)
+                .takes_value(false)
+                .help("Do not print the @HD header line"),
+        )
+}
+
+/// Run dict workflow
+pub fn run(matches: &clap::ArgMatches) -> Result<()> {
+    let file = matches
+        .value_of(FILE_ARG)
+        .ok_or_else(|| Error::new(ErrorKind::User, "file argument required"))?;
+    let uri = match matches.value_of(URI_ARG) {
+        Some(uri) => uri.to_string(),
+        None => default_uri(file)?,
+    };
+    let output: Box<dyn std::io::Write> = match matches.value_of(OUTPUT_ARG) {
+        Some(output) => Box::new(std::fs::File::create(output)?),
+        None => Box::new(std::io::stdout()),
+    };
+    let mut writer = dict::Writer::new(std::io::BufWriter::new(output));
+    if !matches.is_present(NO_HEADER_FLAG) {
+        writer.write_header()?;
+    }
+    let indexer = fai::Indexer::from_path(file, fai::IndexerFormat::FASTA)?.with_md5();
+    for result in indexer.iter() {
+        let record = result?;
+        writer.write(&dict::Record {
+            name: record.name,
+            length: record.length,
+            md5: record.md5,
+            assembly: matches.value_of(ASSEMBLY_ARG).map(String::from),
+            species: matches.value_of(SPECIES_ARG).map(String::from),
+            uri: Some(uri.clone()),
+        })?;
+    }
+    writer.flush()
+}
+
+/// Default URI of a file, a `file://` URI with its absolute path
+fn default_uri(file: &str) -> Result<String> {
+    let path = std::fs::canonicalize(file)?;
+    Ok(format!("{}{}", FILE_URI_PREFIX, path.display()))
+}
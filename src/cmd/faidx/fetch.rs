@@ -0,0 +1,183 @@
+use super::{
+    get_access, get_file, get_index_file, get_length, get_mark_strand, get_output, get_regions,
+    index, Output, REVERSE_COMPLEMENT_FLAG, STALE_INDEX_ARG, STALE_INDEX_REBUILD,
+};
+use crate::errors::{Error, ErrorKind, Result};
+use crate::io::{
+    fai, fasta, fastq,
+    region::{Entry, Region},
+    sequence,
+    source::{self, Access, Source},
+};
+use std::io::{Read, Seek};
+
+const NUM_SAMPLES: usize = 16;
+
+/// Run the region retrieval workflow
+///
+/// The index is built first if it does not exist yet, and checked for staleness otherwise.
+/// Regions are written as FASTA, or as FASTQ records with their qualities.
+///
+pub fn run(matches: &clap::ArgMatches, format: fai::IndexerFormat, output: Output) -> Result<()> {
+    let file = get_file(matches)?;
+    let reverse_complement = matches.is_present(REVERSE_COMPLEMENT_FLAG);
+    let mark_strand = get_mark_strand(matches)?;
+    let line_width = get_length(matches)?;
+    let mut reader = open_reader(matches, file, format)?;
+    let writer = std::io::BufWriter::new(get_output(matches)?);
+    let mut writer = match output {
+        Output::Fasta => Writer::Fasta(fasta::Writer::new(writer, line_width)),
+        Output::Fastq => Writer::Fastq(fastq::Writer::new(writer, line_width)),
+    };
+    for region in get_regions(matches)? {
+        let region = region?;
+        let (name, interval) = resolve_region(&reader, &region)?;
+        let region = format!("{}{}", region, mark_strand.suffix(reverse_complement));
+        match &mut writer {
+            Writer::Fasta(writer) => {
+                let bases = bases(&mut reader, &name, interval, reverse_complement)?;
+                writer.write(&region, bases)?;
+            }
+            Writer::Fastq(writer) => {
+                let mut sequence = Vec::new();
+                bases(&mut reader, &name, interval, reverse_complement)?
+                    .read_to_end(&mut sequence)?;
+                let qualities = qualities(&mut reader, &name, interval, reverse_complement)?;
+                writer.write(&region, &sequence[..], qualities)?;
+            }
+        }
+    }
+    match &mut writer {
+        Writer::Fasta(writer) => writer.flush(),
+        Writer::Fastq(writer) => writer.flush(),
+    }
+}
+
+/// Writer for extracted sequences
+enum Writer<W: std::io::Write> {
+    Fasta(fasta::Writer<W>),
+    Fastq(fastq::Writer<W>),
+}
+
+/// Open the indexed input, building or rebuilding its index as needed
+///
+/// An existing index is stale if it or the `.gzi` block index of BGZF input is older than the
+/// input, or if a sample of its records does not match the input.  A stale index is rebuilt,
+/// along with the `.gzi`, with `--stale-index rebuild`, and is an error otherwise.
+///
+fn open_reader(
+    matches: &clap::ArgMatches,
+    file: &str,
+    format: fai::IndexerFormat,
+) -> Result<fai::IndexedReader<Source>> {
+    let index_file = get_index_file(matches, file);
+    let access = get_access(matches);
+    if !std::path::Path::new(&index_file).exists() {
+        index::build_index(matches, format)?;
+        return fai::IndexedReader::from_path_with(file, &index_file, access);
+    }
+    let error = match check_index(file, &index_file, access) {
+        Ok(reader) => return Ok(reader),
+        Err(error) if error.kind == ErrorKind::Input => error,
+        Err(error) => return Err(error),
+    };
+    if matches.value_of(STALE_INDEX_ARG) != Some(STALE_INDEX_REBUILD) {
+        return Err(Error {
+            message: format!(
+                "stale index {}: {}; rebuild it or use --{} {}",
+                index_file, error.message, STALE_INDEX_ARG, STALE_INDEX_REBUILD
+            ),
+            ..error
+        });
+    }
+    eprintln!("warning: rebuilding stale index {}: {}", index_file, error);
+    index::build_index(matches, format)?;
+    fai::IndexedReader::from_path_with(file, &index_file, access)
+}
+
+/// Open the indexed input, returning an Input error if the index is stale
+fn check_index(file: &str, index_file: &str, access: Access) -> Result<fai::IndexedReader<Source>> {
+    let modified = std::fs::metadata(file)?.modified()?;
+    let index_modified = std::fs::metadata(index_file)?.modified()?;
+    if index_modified < modified {
+        return Err(Error::new(ErrorKind::Input, "index is older than input").with_path(file));
+    }
+    let gzi_path = source::gzi_path(file);
+    if gzi_path.exists() && std::fs::metadata(&gzi_path)?.modified()? < modified {
+        return Err(
+            Error::new(ErrorKind::Input, "block index is older than input").with_path(gzi_path),
+        );
+    }
+    let mut reader = fai::IndexedReader::from_path_with(file, index_file, access)?;
+    reader
+        .validate(NUM_SAMPLES)
+        .map_err(|e| e.with_path(file))?;
+    Ok(reader)
+}
+
+/// Find the sequence name and 0-based interval for a region
+///
+/// A region in samtools syntax that exactly matches a sequence name refers to the whole sequence,
+/// even if it contains a `:`.
+///
+fn resolve_region<R>(
+    reader: &fai::IndexedReader<R>,
+    entry: &Entry,
+) -> Result<(String, (usize, usize))>
+where
+    R: Read + Seek,
+{
+    let region = match entry {
+        Entry::Text(text) if reader.record(text).is_some() => Region::whole(text),
+        Entry::Text(text) => text.parse()?,
+        Entry::Bed(region) => region.clone(),
+    };
+    let record = reader.record(&region.name).ok_or_else(|| {
+        Error::new(
+            ErrorKind::User,
+            &format!("sequence {} not found in index", region.name),
+        )
+    })?;
+    let interval = region.interval(record.length)?;
+    Ok((region.name, interval))
+}
+
+/// Read the bases of an interval, reverse complemented if requested
+///
+/// Forward bases are streamed.  Reverse complemented bases are buffered since they are written
+/// back to front.
+///
+fn bases<'a, R>(
+    reader: &'a mut fai::IndexedReader<R>,
+    name: &str,
+    (start, end): (usize, usize),
+    reverse_complement: bool,
+) -> Result<Box<dyn Read + 'a>>
+where
+    R: Read + Seek,
+{
+    if !reverse_complement {
+        return Ok(Box::new(reader.fetch_reader(name, start, end)?));
+    }
+    let mut bases = reader.fetch(name, start, end)?;
+    sequence::reverse_complement(&mut bases);
+    Ok(Box::new(std::io::Cursor::new(bases)))
+}
+
+/// Read the qualities of an interval, reversed if requested
+fn qualities<'a, R>(
+    reader: &'a mut fai::IndexedReader<R>,
+    name: &str,
+    (start, end): (usize, usize),
+    reverse: bool,
+) -> Result<Box<dyn Read + 'a>>
+where
+    R: Read + Seek,
+{
+    if !reverse {
+        return Ok(Box::new(reader.fetch_quality_reader(name, start, end)?));
+    }
+    let mut qualities = reader.fetch_quality(name, start, end)?;
+    qualities.reverse();
+    Ok(Box::new(std::io::Cursor::new(qualities)))
+}
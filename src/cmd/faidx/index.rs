@@ -1,48 +1,39 @@
-use super::{get_file, output_name, FASTQ_FLAG};
+use super::{get_access, get_duplicates, get_file, get_index_file, get_strictness, get_threads};
 use crate::errors::Result;
-use crate::io::fai;
+use crate::io::{fai, source};
 use std::fs::File;
 
 /// Run the indexing workflow
-pub fn run(matches: &clap::ArgMatches) -> Result<()> {
-    let file = get_file(matches)?;
-    let fastq = matches.is_present(FASTQ_FLAG);
-    let reader = build_reader(file, fastq)?;
-    let mut writer = fai::Writer::new(File::create(output_name(file))?);
-    consume_reader(reader, &mut writer)
-}
-
-/// Build the appropriate Fai reader
-///
-/// If `fastq` is true, then return a FASTQ Fai record reader.  Otherwise, return a FASTA Fai
-/// record.
-///
-fn build_reader(file: &str, fastq: bool) -> Result<fai::Indexer<File>> {
-    let format = if fastq {
-        fai::IndexerFormat::FASTQ
-    } else {
-        fai::IndexerFormat::FASTA
-    };
-    fai::Indexer::from_path(file, format)
+pub fn run(matches: &clap::ArgMatches, format: fai::IndexerFormat) -> Result<()> {
+    build_index(matches, format)
 }
 
-/// Consume a reader and write to output
+/// Index the input file and write its Fai records to the index file
 ///
-/// Duplicate sequence names are ignored.
+/// Records with duplicate names are handled according to `--duplicates`, with a warning on
+/// stderr for each record that is dropped or renamed.  For BGZF compressed input, the `.gzi`
+/// block index is written as well.
 ///
-fn consume_reader<W>(reader: fai::Indexer<File>, writer: &mut fai::Writer<W>) -> Result<()>
-where
-    W: std::io::Write,
-{
-    let mut names = std::collections::HashSet::<String>::new();
-    for result in reader.iter() {
-        let record = result?;
-        if names.contains(&record.name) {
-            println!("duplicate entry: {}, skipping", &record.name);
-            continue;
-        }
-        writer.write(&record)?;
-        names.insert(record.name);
+pub fn build_index(matches: &clap::ArgMatches, format: fai::IndexerFormat) -> Result<()> {
+    let file = get_file(matches)?;
+    let index_file = get_index_file(matches, file);
+    let mut indexer = fai::Indexer::from_path_with(file, format, get_access(matches))?
+        .with_strictness(get_strictness(matches)?)
+        .with_threads(get_threads(matches)?);
+    let (records, warnings) = indexer.index(get_duplicates(matches)?)?;
+    for warning in warnings {
+        eprintln!("warning: {}", warning);
+    }
+    let mut writer = fai::Writer::new(File::create(&index_file)?);
+    for record in records.iter() {
+        writer.write(record)?;
+    }
+    writer.flush()?;
+    let mut source = indexer.into_inner();
+    if let Some(index) = source.gzi_index()? {
+        index.write(std::io::BufWriter::new(File::create(source::gzi_path(
+            file,
+        ))?))?;
     }
     Ok(())
 }
@@ -1,28 +1,166 @@
 use crate::errors::{Error, ErrorKind, Result};
+use crate::io::{fai, fasta, fastq, region, source};
 
+mod fetch;
 mod index;
+mod strand;
 
 pub const SUBCOMMAND: &str = "faidx";
 const FILE_ARG: &str = "file";
+const REGION_ARG: &str = "region";
+const REGION_FILE_ARG: &str = "region-file";
+const REGION_FILE_ARG_SHORT: char = 'r';
+const REVERSE_COMPLEMENT_FLAG: &str = "reverse-complement";
+const REVERSE_COMPLEMENT_FLAG_SHORT: char = 'i';
+const MARK_STRAND_ARG: &str = "mark-strand";
+const MARK_STRAND_DEFAULT: &str = "rc";
+const LENGTH_ARG: &str = "length";
+const LENGTH_ARG_SHORT: char = 'n';
+const FAI_IDX_ARG: &str = "fai-idx";
+const OUTPUT_ARG: &str = "output";
+const OUTPUT_ARG_SHORT: char = 'o';
+const DUPLICATES_ARG: &str = "duplicates";
+const DUPLICATES_DEFAULT: &str = "error";
+const STALE_INDEX_ARG: &str = "stale-index";
+const STALE_INDEX_DEFAULT: &str = "error";
+const STALE_INDEX_REBUILD: &str = "rebuild";
+const STRICTNESS_ARG: &str = "strictness";
+const STRICTNESS_DEFAULT: &str = "strict";
+const THREADS_ARG: &str = "threads";
+const MMAP_FLAG: &str = "mmap";
 const FASTQ_FLAG: &str = "fastq";
 const FASTQ_FLAG_SHORT: char = 'f';
 const SUFFIX: &str = ".fai";
 
 /// faidx subcommand
 pub fn command() -> clap::Command<'static> {
-    clap::Command::new(SUBCOMMAND)
+    arguments(clap::Command::new(SUBCOMMAND)).arg(
+        clap::Arg::new(FASTQ_FLAG)
+            .long(FASTQ_FLAG)
+            .short(FASTQ_FLAG_SHORT)
+            .takes_value(false),
+    )
+}
+
+/// Add the arguments shared by faidx and fqidx
+pub fn arguments(command: clap::Command<'static>) -> clap::Command<'static> {
+    command
         .arg(clap::Arg::new(FILE_ARG).required(true))
+        .arg(clap::Arg::new(REGION_ARG).multiple_values(true))
+        .arg(
+            clap::Arg::new(REGION_FILE_ARG)
+                .long(REGION_FILE_ARG)
+                .short(REGION_FILE_ARG_SHORT)
+                .takes_value(true)
+                .help("File of regions, one per line"),
+        )
+        .arg(
+            clap::Arg::new(REVERSE_COMPLEMENT_FLAG)
+                .long(REVERSE_COMPLEMENT_FLAG)
+                .short(REVERSE_COMPLEMENT_FLAG_SHORT)
+                .takes_value(false)
+                .help("Reverse complement extracted sequences"),
+        )
+        .arg(
+            clap::Arg::new(MARK_STRAND_ARG)
+                .long(MARK_STRAND_ARG)
+                .takes_value(true)
+                .default_value(MARK_STRAND_DEFAULT)
+                .help("Strand marking for names: rc, no, sign or custom,<pos>,<neg>"),
+        )
+        .arg(
+            clap::Arg::new(LENGTH_ARG)
+                .long(LENGTH_ARG)
+                .short(LENGTH_ARG_SHORT)
+                .takes_value(true)
+                .help("Length of output lines, 0 for no wrapping [default: 60]"),
+        )
+        .arg(
+            clap::Arg::new(FAI_IDX_ARG)
+                .long(FAI_IDX_ARG)
+                .takes_value(true)
+                .help("Path of the index file to read or write [default: <file>.fai]"),
+        )
         .arg(
-            clap::Arg::new(FASTQ_FLAG)
-                .long(FASTQ_FLAG)
-                .short(FASTQ_FLAG_SHORT)
-                .takes_value(false),
+            clap::Arg::new(OUTPUT_ARG)
+                .long(OUTPUT_ARG)
+                .short(OUTPUT_ARG_SHORT)
+                .takes_value(true)
+                .help("Output file for extracted sequences [default: stdout]"),
+        )
+        .arg(
+            clap::Arg::new(DUPLICATES_ARG)
+                .long(DUPLICATES_ARG)
+                .takes_value(true)
+                .possible_values(["error", "skip", "keep-first", "rename"])
+                .default_value(DUPLICATES_DEFAULT)
+                .help("Handling of duplicate sequence names when indexing"),
+        )
+        .arg(
+            clap::Arg::new(STRICTNESS_ARG)
+                .long(STRICTNESS_ARG)
+                .takes_value(true)
+                .possible_values([STRICTNESS_DEFAULT, "lenient"])
+                .default_value(STRICTNESS_DEFAULT)
+                .help("FASTQ validation: strict also checks the name on the '+' line"),
+        )
+        .arg(
+            clap::Arg::new(THREADS_ARG)
+                .long(THREADS_ARG)
+                .takes_value(true)
+                .help("Number of threads for indexing uncompressed FASTA [default: 1]"),
+        )
+        .arg(
+            clap::Arg::new(MMAP_FLAG)
+                .long(MMAP_FLAG)
+                .help("Memory map uncompressed input instead of reading it"),
+        )
+        .arg(
+            clap::Arg::new(STALE_INDEX_ARG)
+                .long(STALE_INDEX_ARG)
+                .takes_value(true)
+                .possible_values([STALE_INDEX_DEFAULT, STALE_INDEX_REBUILD])
+                .default_value(STALE_INDEX_DEFAULT)
+                .help("Handling of an index that is older than or does not match the input"),
         )
 }
 
+/// Format of extracted sequences
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Output {
+    /// Bases only
+    Fasta,
+    /// Bases and qualities, which requires FASTQ input
+    Fastq,
+}
+
 /// Run faidx workflow
+///
+/// Regions of FASTQ input are written as FASTQ.
+///
 pub fn run(matches: &clap::ArgMatches) -> Result<()> {
-    index::run(matches)
+    if matches.is_present(FASTQ_FLAG) {
+        run_format(matches, fai::IndexerFormat::FASTQ, Output::Fastq)
+    } else {
+        run_format(matches, fai::IndexerFormat::FASTA, Output::Fasta)
+    }
+}
+
+/// Run faidx workflow for the given input and output formats
+///
+/// If regions are given, the sequence of each region is written to the output.  Otherwise, the
+/// input is indexed.
+///
+pub fn run_format(
+    matches: &clap::ArgMatches,
+    format: fai::IndexerFormat,
+    output: Output,
+) -> Result<()> {
+    if matches.is_present(REGION_ARG) || matches.is_present(REGION_FILE_ARG) {
+        fetch::run(matches, format, output)
+    } else {
+        index::run(matches, format)
+    }
 }
 
 /// Get file argument
@@ -32,7 +170,96 @@ fn get_file(matches: &clap::ArgMatches) -> Result<&str> {
         .ok_or_else(|| Error::new(ErrorKind::User, "file argument required"))
 }
 
-/// Output name for index file
-fn output_name(file: &str) -> String {
-    format!("{}{}", file, SUFFIX)
+/// Get regions from the command line followed by the region file
+///
+/// Regions from the region file are read lazily so that large files are streamed.
+///
+fn get_regions<'a>(
+    matches: &'a clap::ArgMatches,
+) -> Result<Box<dyn Iterator<Item = Result<region::Entry>> + 'a>> {
+    let regions = matches
+        .values_of(REGION_ARG)
+        .into_iter()
+        .flatten()
+        .map(|region| Ok(region::Entry::Text(region.to_string())));
+    match matches.value_of(REGION_FILE_ARG) {
+        Some(file) => {
+            let file = std::io::BufReader::new(std::fs::File::open(file)?);
+            Ok(Box::new(regions.chain(region::Reader::new(file))))
+        }
+        None => Ok(Box::new(regions)),
+    }
+}
+
+/// Get the strand marking
+fn get_mark_strand(matches: &clap::ArgMatches) -> Result<strand::MarkStrand> {
+    matches
+        .value_of(MARK_STRAND_ARG)
+        .unwrap_or(MARK_STRAND_DEFAULT)
+        .parse()
+}
+
+/// Get the output line length
+fn get_length(matches: &clap::ArgMatches) -> Result<usize> {
+    match matches.value_of(LENGTH_ARG) {
+        Some(length) => length
+            .parse()
+            .map_err(|_| Error::new(ErrorKind::User, &format!("invalid line length: {}", length))),
+        None => Ok(fasta::DEFAULT_LINE_WIDTH),
+    }
+}
+
+/// Get the duplicate sequence name policy
+fn get_duplicates(matches: &clap::ArgMatches) -> Result<fai::Duplicates> {
+    matches
+        .value_of(DUPLICATES_ARG)
+        .unwrap_or(DUPLICATES_DEFAULT)
+        .parse()
+}
+
+/// Get the FASTQ validation strictness
+fn get_strictness(matches: &clap::ArgMatches) -> Result<fastq::Strictness> {
+    matches
+        .value_of(STRICTNESS_ARG)
+        .unwrap_or(STRICTNESS_DEFAULT)
+        .parse()
+}
+
+/// Get the number of indexing threads
+fn get_threads(matches: &clap::ArgMatches) -> Result<usize> {
+    match matches.value_of(THREADS_ARG) {
+        Some(threads) => match threads.parse() {
+            Ok(threads) if threads > 0 => Ok(threads),
+            _ => Err(Error::new(
+                ErrorKind::User,
+                &format!("invalid number of threads: {}", threads),
+            )),
+        },
+        None => Ok(1),
+    }
+}
+
+/// Get how uncompressed input is read
+fn get_access(matches: &clap::ArgMatches) -> source::Access {
+    if matches.is_present(MMAP_FLAG) {
+        source::Access::Mmap
+    } else {
+        source::Access::Read
+    }
+}
+
+/// Get the index path, which defaults to the input path with a `.fai` suffix
+fn get_index_file(matches: &clap::ArgMatches, file: &str) -> String {
+    match matches.value_of(FAI_IDX_ARG) {
+        Some(index_file) => index_file.to_string(),
+        None => format!("{}{}", file, SUFFIX),
+    }
+}
+
+/// Get the output for extracted sequences, which defaults to stdout
+fn get_output(matches: &clap::ArgMatches) -> Result<Box<dyn std::io::Write>> {
+    match matches.value_of(OUTPUT_ARG) {
+        Some(output) => Ok(Box::new(std::fs::File::create(output)?)),
+        None => Ok(Box::new(std::io::stdout())),
+    }
 }
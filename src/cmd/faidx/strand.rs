@@ -0,0 +1,146 @@
+use crate::errors::{Error, ErrorKind, Result};
+
+const RC: &str = "rc";
+const NO: &str = "no";
+const SIGN: &str = "sign";
+const CUSTOM: &str = "custom";
+const CUSTOM_DELIMITER: char = ',';
+
+/// MarkStrand controls how the strand is marked in the names of extracted regions
+#[derive(Debug, PartialEq)]
+pub enum MarkStrand {
+    /// Append `/rc` to reverse complemented regions
+    Rc,
+    /// Leave names unchanged
+    No,
+    /// Append `(+)` or `(-)`
+    Sign,
+    /// Append custom text for the positive and negative strands
+    Custom(String, String),
+}
+
+impl MarkStrand {
+    /// Text to append to a region name
+    pub fn suffix(&self, reverse_complement: bool) -> &str {
+        match (self, reverse_complement) {
+            (Self::Rc, true) => "/rc",
+            (Self::Rc, false) | (Self::No, _) => "",
+            (Self::Sign, true) => "(-)",
+            (Self::Sign, false) => "(+)",
+            (Self::Custom(_, negative), true) => negative,
+            (Self::Custom(positive, _), false) => positive,
+        }
+    }
+}
+
+impl std::str::FromStr for MarkStrand {
+    type Err = Error;
+
+    /// Parse a strand marking as `rc`, `no`, `sign` or `custom,<pos>,<neg>`
+    ///
+    /// The custom texts may be empty but may not contain `,`.
+    ///
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            RC => return Ok(Self::Rc),
+            NO => return Ok(Self::No),
+            SIGN => return Ok(Self::Sign),
+            _ => {}
+        }
+        let mut parts = s.split(CUSTOM_DELIMITER);
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(CUSTOM), Some(positive), Some(negative), None) => {
+                Ok(Self::Custom(positive.into(), negative.into()))
+            }
+            _ => Err(Error::new(
+                ErrorKind::User,
+                &format!("invalid strand marking: {}", s),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mark_strand() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a str,
+            expect_error: bool,
+            expected: (&'a str, &'a str),
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should mark reverse complemented regions with rc",
+                input: "rc",
+                expect_error: false,
+                expected: ("", "/rc"),
+            },
+            TestCase {
+                name: "Should not mark regions with no",
+                input: "no",
+                expect_error: false,
+                expected: ("", ""),
+            },
+            TestCase {
+                name: "Should mark both strands with sign",
+                input: "sign",
+                expect_error: false,
+                expected: ("(+)", "(-)"),
+            },
+            TestCase {
+                name: "Should mark both strands with custom text",
+                input: "custom,_fwd,_rev",
+                expect_error: false,
+                expected: ("_fwd", "_rev"),
+            },
+            TestCase {
+                name: "Should allow empty custom text",
+                input: "custom,,_rev",
+                expect_error: false,
+                expected: ("", "_rev"),
+            },
+            TestCase {
+                name: "Should reject custom without texts",
+                input: "custom",
+                expect_error: true,
+                expected: ("", ""),
+            },
+            TestCase {
+                name: "Should reject custom with a missing field",
+                input: "custom,_fwd",
+                expect_error: true,
+                expected: ("", ""),
+            },
+            TestCase {
+                name: "Should reject custom with an extra field",
+                input: "custom,_fwd,_rev,_extra",
+                expect_error: true,
+                expected: ("", ""),
+            },
+            TestCase {
+                name: "Should reject an unknown value",
+                input: "both",
+                expect_error: true,
+                expected: ("", ""),
+            },
+        ];
+        for test_case in test_cases {
+            let actual = test_case.input.parse::<MarkStrand>();
+            if test_case.expect_error {
+                assert!(actual.is_err(), "{}", test_case.name);
+            } else {
+                let actual = actual.unwrap();
+                assert_eq!(
+                    test_case.expected,
+                    (actual.suffix(false), actual.suffix(true)),
+                    "{}",
+                    test_case.name
+                );
+            }
+        }
+    }
+}
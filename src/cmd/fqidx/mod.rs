@@ -0,0 +1,40 @@
+use super::faidx;
+use crate::errors::Result;
+use crate::io::fai;
+
+pub const SUBCOMMAND: &str = "fqidx";
+const FASTA_FLAG: &str = "fasta";
+const FASTQ_FLAG: &str = "fastq";
+
+/// fqidx subcommand
+pub fn command() -> clap::Command<'static> {
+    faidx::arguments(clap::Command::new(SUBCOMMAND))
+        .about("Index a FASTQ file or extract reads from it")
+        .arg(
+            clap::Arg::new(FASTA_FLAG)
+                .long(FASTA_FLAG)
+                .takes_value(false)
+                .conflicts_with(FASTQ_FLAG)
+                .help("Write extracted reads as FASTA"),
+        )
+        .arg(
+            clap::Arg::new(FASTQ_FLAG)
+                .long(FASTQ_FLAG)
+                .takes_value(false)
+                .help("Write extracted reads as FASTQ [default]"),
+        )
+}
+
+/// Run fqidx workflow
+///
+/// The input is always read as FASTQ.  Extracted reads are written as FASTQ unless `--fasta` is
+/// given.
+///
+pub fn run(matches: &clap::ArgMatches) -> Result<()> {
+    let output = if matches.is_present(FASTA_FLAG) {
+        faidx::Output::Fasta
+    } else {
+        faidx::Output::Fastq
+    };
+    faidx::run_format(matches, fai::IndexerFormat::FASTQ, output)
+}
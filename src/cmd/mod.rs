@@ -1,6 +1,8 @@
 use crate::errors::{Error, ErrorKind, Result};
 
+mod dict;
 mod faidx;
+mod fqidx;
 
 /// Run the command line
 pub fn run() -> Result<()> {
@@ -8,12 +10,16 @@ pub fn run() -> Result<()> {
         .author("Mimi Wang, mimikwang@gmail.com")
         .version("0.1.0")
         .about("Rust implementation of samtools")
+        .subcommand(dict::command())
         .subcommand(faidx::command())
+        .subcommand(fqidx::command())
         .subcommand_required(true)
         .get_matches();
 
     match matches.subcommand() {
+        Some((dict::SUBCOMMAND, matches)) => dict::run(matches),
         Some((faidx::SUBCOMMAND, matches)) => faidx::run(matches),
+        Some((fqidx::SUBCOMMAND, matches)) => fqidx::run(matches),
         Some((subcommand, _)) => Err(Error::new(
             ErrorKind::User,
             &format!("unrecognized command {}", subcommand),
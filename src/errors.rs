@@ -5,19 +5,38 @@ pub type Result<T> = std::result::Result<T, Error>;
 ///
 /// All external error types should be converted to `Error`.
 ///
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Error {
     pub kind: ErrorKind,
     pub message: String,
+    pub location: Location,
+}
+
+/// Location in an input where an error occurred
+///
+/// Every field is optional since not every error can be traced back to every detail.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Location {
+    /// Path of the input file
+    pub path: Option<std::path::PathBuf>,
+    /// 1-based line number
+    pub line: Option<usize>,
+    /// Byte offset from the start of the input
+    pub offset: Option<u64>,
+    /// Name of the record being read
+    pub record: Option<String>,
 }
 
 /// Kind of error
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub enum ErrorKind {
     /// Input format errors
     Input,
     /// Errors related to input / output
     IO,
+    /// Input compressed in a format that can't be read
+    Compression,
     /// End of file errors
     Eof,
     /// Type conversion errors
@@ -25,20 +44,28 @@ pub enum ErrorKind {
     /// User related errors
     User,
     /// All other errors
+    #[default]
     Unknown,
 }
 
-impl Default for ErrorKind {
-    fn default() -> Self {
-        Self::Unknown
-    }
-}
-
 impl std::error::Error for Error {}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "kind: {:?}, message: {}", self.kind, self.message)
+        write!(f, "kind: {:?}, message: {}", self.kind, self.message)?;
+        if let Some(path) = &self.location.path {
+            write!(f, ", path: {}", path.display())?;
+        }
+        if let Some(line) = self.location.line {
+            write!(f, ", line: {}", line)?;
+        }
+        if let Some(offset) = self.location.offset {
+            write!(f, ", offset: {}", offset)?;
+        }
+        if let Some(record) = &self.location.record {
+            write!(f, ", record: {}", record)?;
+        }
+        Ok(())
     }
 }
 
@@ -48,19 +75,74 @@ impl Error {
         Error {
             kind,
             message: message.into(),
+            location: Location::default(),
         }
     }
+
+    /// Set the path of the input, unless it is already set
+    ///
+    /// Like the other location setters, this keeps the value set closest to where the error
+    /// occurred.
+    ///
+    pub fn with_path<P: AsRef<std::path::Path>>(mut self, path: P) -> Self {
+        self.location
+            .path
+            .get_or_insert_with(|| path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Set the 1-based line number, unless it is already set
+    pub fn with_line(mut self, line: usize) -> Self {
+        self.location.line.get_or_insert(line);
+        self
+    }
+
+    /// Set the byte offset, unless it is already set
+    pub fn with_offset(mut self, offset: u64) -> Self {
+        self.location.offset.get_or_insert(offset);
+        self
+    }
+
+    /// Set the name of the record being read, unless it is already set or `record` is empty
+    pub fn with_record(mut self, record: &str) -> Self {
+        if !record.is_empty() {
+            self.location.record.get_or_insert_with(|| record.into());
+        }
+        self
+    }
 }
 
 impl From<csv::Error> for Error {
     fn from(e: csv::Error) -> Self {
-        Self::new(ErrorKind::Input, &e.to_string())
+        let error = Self::new(ErrorKind::Input, &e.to_string());
+        match e.position() {
+            Some(position) => error
+                .with_line(position.line() as usize)
+                .with_offset(position.byte()),
+            None => error,
+        }
     }
 }
 
 impl From<std::io::Error> for Error {
+    /// Convert an IO error, unwrapping an `Error` that was converted to an IO error
     fn from(e: std::io::Error) -> Self {
-        Self::new(ErrorKind::IO, &e.to_string())
+        match e.get_ref().and_then(|inner| inner.downcast_ref::<Error>()) {
+            Some(inner) => inner.clone(),
+            None => Self::new(ErrorKind::IO, &e.to_string()),
+        }
+    }
+}
+
+impl From<Error> for std::io::Error {
+    /// Convert an `Error` to an IO error so it can be returned from `std::io` traits
+    fn from(e: Error) -> Self {
+        let kind = match e.kind {
+            ErrorKind::Input => std::io::ErrorKind::InvalidData,
+            ErrorKind::Eof => std::io::ErrorKind::UnexpectedEof,
+            _ => std::io::ErrorKind::Other,
+        };
+        std::io::Error::new(kind, e)
     }
 }
 
@@ -75,3 +157,61 @@ impl From<std::str::Utf8Error> for Error {
         Self::new(ErrorKind::TypeConversion, &e.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_display() {
+        struct TestCase<'a> {
+            name: &'a str,
+            error: Error,
+            expected: &'a str,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should display an error without a location",
+                error: Error::new(ErrorKind::Input, "invalid input format"),
+                expected: "kind: Input, message: invalid input format",
+            },
+            TestCase {
+                name: "Should display the location",
+                error: Error::new(ErrorKind::Input, "invalid input format")
+                    .with_record("chr1")
+                    .with_offset(120)
+                    .with_line(3)
+                    .with_path("ref.fa"),
+                expected: "kind: Input, message: invalid input format, path: ref.fa, line: 3, \
+                           offset: 120, record: chr1",
+            },
+        ];
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                test_case.error.to_string(),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_location_keeps_first_value() {
+        let error = Error::new(ErrorKind::Input, "invalid input format")
+            .with_line(3)
+            .with_line(10)
+            .with_record("")
+            .with_record("chr1");
+        assert_eq!(
+            Some(3),
+            error.location.line,
+            "Should keep the line closest to the error",
+        );
+        assert_eq!(
+            Some("chr1".to_string()),
+            error.location.record,
+            "Should ignore empty record names",
+        );
+    }
+}
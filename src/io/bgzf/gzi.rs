@@ -0,0 +1,182 @@
+use crate::errors::{Error, ErrorKind, Result};
+
+/// Entry is the start of a BGZF block
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Entry {
+    /// Offset of the block in the compressed file
+    pub compressed: u64,
+    /// Offset of the block's first byte in the uncompressed data
+    pub uncompressed: u64,
+}
+
+/// Index maps uncompressed offsets to BGZF blocks, as stored in `.gzi` files
+///
+/// The `.gzi` format is a little endian `u64` count followed by a pair of little endian `u64`
+/// compressed and uncompressed offsets for each block.  The first block always starts at offset
+/// 0 in both and is not stored.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Index {
+    entries: Vec<Entry>,
+}
+
+impl Default for Index {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Index {
+    /// Construct an index holding only the first block
+    pub fn new() -> Self {
+        Self {
+            entries: vec![Entry::default()],
+        }
+    }
+
+    /// Block entries in file order, including the first block
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Add the start of a block
+    ///
+    /// Blocks must be added in file order.
+    ///
+    pub fn push(&mut self, entry: Entry) {
+        self.entries.push(entry);
+    }
+
+    /// Find the block containing the uncompressed offset
+    pub fn locate(&self, uncompressed: u64) -> Entry {
+        let i = self
+            .entries
+            .partition_point(|entry| entry.uncompressed <= uncompressed);
+        self.entries[i.saturating_sub(1)]
+    }
+
+    /// Read an index in `.gzi` format
+    pub fn read<R>(mut reader: R) -> Result<Self>
+    where
+        R: std::io::Read,
+    {
+        let count = read_u64(&mut reader)?;
+        let mut index = Self::new();
+        for _ in 0..count {
+            let compressed = read_u64(&mut reader)?;
+            let uncompressed = read_u64(&mut reader)?;
+            if uncompressed < index.entries[index.entries.len() - 1].uncompressed {
+                return Err(Error::new(ErrorKind::Input, "invalid gzi index: unsorted"));
+            }
+            index.entries.push(Entry {
+                compressed,
+                uncompressed,
+            });
+        }
+        Ok(index)
+    }
+
+    /// Write the index in `.gzi` format
+    pub fn write<W>(&self, mut writer: W) -> Result<()>
+    where
+        W: std::io::Write,
+    {
+        let entries = &self.entries[1..];
+        writer.write_all(&(entries.len() as u64).to_le_bytes())?;
+        for entry in entries {
+            writer.write_all(&entry.compressed.to_le_bytes())?;
+            writer.write_all(&entry.uncompressed.to_le_bytes())?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Read a little endian u64
+fn read_u64<R>(reader: &mut R) -> Result<u64>
+where
+    R: std::io::Read,
+{
+    let mut buffer = [0; 8];
+    reader.read_exact(&mut buffer)?;
+    Ok(u64::from_le_bytes(buffer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> Index {
+        let mut index = Index::new();
+        index.push(Entry {
+            compressed: 100,
+            uncompressed: 1000,
+        });
+        index.push(Entry {
+            compressed: 200,
+            uncompressed: 2000,
+        });
+        index
+    }
+
+    #[test]
+    fn test_index_read_write() {
+        let index = example();
+        let mut data = Vec::new();
+        assert!(index.write(&mut data).is_ok(), "Should write an index");
+        assert_eq!(8 + 2 * 16, data.len(), "Should not write the first block");
+        assert_eq!(
+            Ok(index),
+            Index::read(data.as_slice()),
+            "Should read back the written index",
+        );
+        assert!(
+            Index::read(&data[..12]).is_err(),
+            "Should return an error on a truncated index",
+        );
+
+        let mut data = 2u64.to_le_bytes().to_vec();
+        for offset in [200u64, 2000, 100, 1000] {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        assert!(
+            Index::read(data.as_slice()).is_err(),
+            "Should return an error on an unsorted index",
+        );
+    }
+
+    #[test]
+    fn test_index_locate() {
+        struct TestCase<'a> {
+            name: &'a str,
+            uncompressed: u64,
+            expected: u64,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should locate the first block",
+                uncompressed: 999,
+                expected: 0,
+            },
+            TestCase {
+                name: "Should locate a block by its first byte",
+                uncompressed: 1000,
+                expected: 100,
+            },
+            TestCase {
+                name: "Should locate the last block",
+                uncompressed: 5000,
+                expected: 200,
+            },
+        ];
+        let index = example();
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                index.locate(test_case.uncompressed).compressed,
+                "{}",
+                test_case.name
+            );
+        }
+    }
+}
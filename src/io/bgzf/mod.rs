@@ -0,0 +1,529 @@
+mod gzi;
+
+use crate::errors::{Error, ErrorKind, Result};
+use std::io::{Read, Seek, SeekFrom};
+
+pub use gzi::{Entry as GziEntry, Index as GziIndex};
+
+const MAGIC: [u8; 4] = [0x1f, 0x8b, 0x08, 0x04];
+const HEADER_LEN: usize = 12;
+const TRAILER_LEN: usize = 8;
+const SUBFIELD_HEADER_LEN: usize = 4;
+const BC_SUBFIELD: [u8; 2] = [b'B', b'C'];
+const MAX_BLOCK_LEN: usize = 1 << 16;
+
+/// Check to see if data starts with a BGZF block header
+pub fn is_bgzf(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN + SUBFIELD_HEADER_LEN
+        && data.starts_with(&MAGIC)
+        && find_bsize(&data[HEADER_LEN..]).is_some()
+}
+
+/// Reader decompresses BGZF data
+///
+/// Reads return uncompressed bytes and seeking is in uncompressed offsets.  Blocks are recorded
+/// in a [`GziIndex`] as they are read so that the index of a file read from start to finish comes
+/// for free.  Seeking past the recorded blocks scans the remaining block headers without
+/// decompressing them.
+///
+pub struct Reader<R>
+where
+    R: Read + Seek,
+{
+    reader: R,
+    reader_position: u64,
+    decompress: flate2::Decompress,
+    compressed: Vec<u8>,
+    block: Vec<u8>,
+    block_position: usize,
+    block_start: GziEntry,
+    next_block: GziEntry,
+    index: GziIndex,
+    indexed_to: GziEntry,
+    index_complete: bool,
+}
+
+impl<R> Reader<R>
+where
+    R: Read + Seek,
+{
+    /// Construct a BGZF reader from `std::io::Read`
+    ///
+    /// The reader is expected to be positioned at the start of the BGZF data.
+    ///
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            reader_position: 0,
+            decompress: flate2::Decompress::new(false),
+            compressed: Vec::new(),
+            block: Vec::new(),
+            block_position: 0,
+            block_start: GziEntry::default(),
+            next_block: GziEntry::default(),
+            index: GziIndex::new(),
+            indexed_to: GziEntry::default(),
+            index_complete: false,
+        }
+    }
+
+    /// Construct a BGZF reader with the complete block index of the data
+    pub fn with_index(reader: R, index: GziIndex) -> Self {
+        Self {
+            index,
+            index_complete: true,
+            ..Self::new(reader)
+        }
+    }
+
+    /// Get the complete block index
+    ///
+    /// Block headers that have not been read yet are scanned first.
+    ///
+    pub fn index(&mut self) -> Result<&GziIndex> {
+        self.scan_to(u64::MAX)?;
+        Ok(&self.index)
+    }
+
+    /// Length of the uncompressed data
+    fn len(&mut self) -> Result<u64> {
+        self.scan_to(u64::MAX)?;
+        let last = match self.index.entries().last() {
+            Some(&last) => last,
+            None => return Ok(0),
+        };
+        self.load_block(last)?;
+        Ok(last.uncompressed + self.block.len() as u64)
+    }
+
+    /// Current offset in the uncompressed data
+    fn position(&self) -> u64 {
+        self.block_start.uncompressed + self.block_position as u64
+    }
+
+    /// Read the block starting at `start` into the block buffer
+    ///
+    /// Returns false at the end of the file.
+    ///
+    fn load_block(&mut self, start: GziEntry) -> Result<bool> {
+        if self.reader_position != start.compressed {
+            self.reader.seek(SeekFrom::Start(start.compressed))?;
+            self.reader_position = start.compressed;
+        }
+        self.block.clear();
+        self.block_position = 0;
+        self.block_start = start;
+        let block_size = match read_block(
+            &mut self.reader,
+            &mut self.decompress,
+            &mut self.compressed,
+            &mut self.block,
+        )? {
+            Some(block_size) => block_size,
+            None => {
+                self.next_block = start;
+                return Ok(false);
+            }
+        };
+        self.reader_position += block_size;
+        self.next_block = GziEntry {
+            compressed: start.compressed + block_size,
+            uncompressed: start.uncompressed + self.block.len() as u64,
+        };
+        if start == self.indexed_to && !self.index_complete {
+            self.index_block(start, self.next_block);
+        }
+        Ok(true)
+    }
+
+    /// Record the block starting at `start`, which is followed by the block at `next`
+    ///
+    /// The first block is always in the index, and empty blocks such as the end of file marker are
+    /// left out, as in the `.gzi` files written by bgzip.
+    ///
+    fn index_block(&mut self, start: GziEntry, next: GziEntry) {
+        if start != GziEntry::default() && next.uncompressed > start.uncompressed {
+            self.index.push(start);
+        }
+        self.indexed_to = next;
+    }
+
+    /// Record block headers until the block containing `uncompressed` is indexed
+    fn scan_to(&mut self, uncompressed: u64) -> Result<()> {
+        while !self.index_complete && self.indexed_to.uncompressed <= uncompressed {
+            let start = self.indexed_to;
+            self.reader.seek(SeekFrom::Start(start.compressed))?;
+            self.reader_position = start.compressed;
+            match read_block_size(&mut self.reader)? {
+                Some((block_size, data_size)) => {
+                    self.reader_position += block_size;
+                    let next = GziEntry {
+                        compressed: start.compressed + block_size,
+                        uncompressed: start.uncompressed + data_size,
+                    };
+                    self.index_block(start, next);
+                }
+                None => self.index_complete = true,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> Read for Reader<R>
+where
+    R: Read + Seek,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.block_position >= self.block.len() {
+            if !self.load_block(self.next_block)? {
+                return Ok(0);
+            }
+        }
+        let num_bytes = buf.len().min(self.block.len() - self.block_position);
+        buf[..num_bytes]
+            .copy_from_slice(&self.block[self.block_position..self.block_position + num_bytes]);
+        self.block_position += num_bytes;
+        Ok(num_bytes)
+    }
+}
+
+impl<R> Seek for Reader<R>
+where
+    R: Read + Seek,
+{
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(target) => Some(target),
+            SeekFrom::Current(delta) => self.position().checked_add_signed(delta),
+            SeekFrom::End(delta) => self.len()?.checked_add_signed(delta),
+        }
+        .ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "invalid seek position")
+        })?;
+        let block_end = self.block_start.uncompressed + self.block.len() as u64;
+        if target >= self.block_start.uncompressed && target < block_end {
+            self.block_position = (target - self.block_start.uncompressed) as usize;
+            return Ok(target);
+        }
+        self.scan_to(target)?;
+        let start = self.index.locate(target);
+        self.load_block(start)?;
+        self.block_position = (target - start.uncompressed) as usize;
+        Ok(target)
+    }
+}
+
+/// Read and decompress a BGZF block
+///
+/// Returns the compressed size of the block, or `None` at the end of the file.
+///
+fn read_block<R>(
+    reader: &mut R,
+    decompress: &mut flate2::Decompress,
+    compressed: &mut Vec<u8>,
+    block: &mut Vec<u8>,
+) -> Result<Option<u64>>
+where
+    R: Read,
+{
+    let (block_size, extra_len) = match read_header(reader)? {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let data_len = block_size
+        .checked_sub(HEADER_LEN + extra_len + TRAILER_LEN)
+        .ok_or_else(invalid_block)?;
+    compressed.resize(data_len + TRAILER_LEN, 0);
+    reader.read_exact(compressed)?;
+    let trailer = &compressed[data_len..];
+    let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+    let size = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]) as usize;
+
+    decompress.reset(false);
+    block.reserve(size.min(MAX_BLOCK_LEN));
+    decompress
+        .decompress_vec(
+            &compressed[..data_len],
+            block,
+            flate2::FlushDecompress::Finish,
+        )
+        .map_err(|_| invalid_block())?;
+    let mut checksum = flate2::Crc::new();
+    checksum.update(block);
+    if block.len() != size || checksum.sum() != crc {
+        return Err(invalid_block());
+    }
+    Ok(Some(block_size as u64))
+}
+
+/// Read the size of a BGZF block without decompressing it
+///
+/// Returns the compressed and uncompressed sizes of the block, or `None` at the end of the file.
+/// The reader is left at the start of the next block.
+///
+fn read_block_size<R>(reader: &mut R) -> Result<Option<(u64, u64)>>
+where
+    R: Read + Seek,
+{
+    let (block_size, extra_len) = match read_header(reader)? {
+        Some(header) => header,
+        None => return Ok(None),
+    };
+    let data_len = block_size
+        .checked_sub(HEADER_LEN + extra_len + TRAILER_LEN)
+        .ok_or_else(invalid_block)?;
+    reader.seek(SeekFrom::Current(data_len as i64 + 4))?;
+    let mut size = [0; 4];
+    reader.read_exact(&mut size)?;
+    Ok(Some((block_size as u64, u32::from_le_bytes(size) as u64)))
+}
+
+/// Read a BGZF block header
+///
+/// Returns the total size of the block and the length of the extra field, or `None` at the end
+/// of the file.
+///
+fn read_header<R>(reader: &mut R) -> Result<Option<(usize, usize)>>
+where
+    R: Read,
+{
+    let mut header = [0; HEADER_LEN];
+    let num_bytes = read_full(reader, &mut header)?;
+    if num_bytes == 0 {
+        return Ok(None);
+    }
+    if num_bytes < HEADER_LEN || !header.starts_with(&MAGIC) {
+        return Err(invalid_block());
+    }
+    let extra_len = u16::from_le_bytes([header[10], header[11]]) as usize;
+    let mut extra = vec![0; extra_len];
+    reader.read_exact(&mut extra)?;
+    let block_size = find_bsize(&extra).ok_or_else(invalid_block)?;
+    Ok(Some((block_size, extra_len)))
+}
+
+/// Find the total block size in the BC subfield of a gzip extra field
+fn find_bsize(mut extra: &[u8]) -> Option<usize> {
+    while extra.len() >= SUBFIELD_HEADER_LEN {
+        let len = u16::from_le_bytes([extra[2], extra[3]]) as usize;
+        let data = extra.get(SUBFIELD_HEADER_LEN..SUBFIELD_HEADER_LEN + len)?;
+        if extra[..2] == BC_SUBFIELD && len == 2 {
+            return Some(u16::from_le_bytes([data[0], data[1]]) as usize + 1);
+        }
+        extra = &extra[SUBFIELD_HEADER_LEN + len..];
+    }
+    None
+}
+
+/// Read until `buffer` is full or the end of the file is reached
+fn read_full<R>(reader: &mut R, buffer: &mut [u8]) -> Result<usize>
+where
+    R: Read,
+{
+    let mut total = 0;
+    while total < buffer.len() {
+        match reader.read(&mut buffer[total..])? {
+            0 => break,
+            num_bytes => total += num_bytes,
+        }
+    }
+    Ok(total)
+}
+
+/// Error for a malformed BGZF block
+fn invalid_block() -> Error {
+    Error::new(ErrorKind::Input, "invalid BGZF block")
+}
+
+/// Compress data into BGZF blocks of at most `block_size` uncompressed bytes
+///
+/// An empty end of file block is appended.
+///
+#[cfg(test)]
+pub fn compress(data: &[u8], block_size: usize) -> Vec<u8> {
+    use std::io::Write;
+
+    let mut output = Vec::new();
+    for chunk in data.chunks(block_size).chain(std::iter::once(&[][..])) {
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(chunk).unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut crc = flate2::Crc::new();
+        crc.update(chunk);
+        let bsize = (HEADER_LEN + 6 + compressed.len() + TRAILER_LEN - 1) as u16;
+        output.extend_from_slice(&MAGIC);
+        output.extend_from_slice(&[0, 0, 0, 0, 0, 0xff, 6, 0, b'B', b'C', 2, 0]);
+        output.extend_from_slice(&bsize.to_le_bytes());
+        output.extend_from_slice(&compressed);
+        output.extend_from_slice(&crc.sum().to_le_bytes());
+        output.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: &[u8] = b"ATGCATGCATGCATGCATGCATGCATGCAT\nGCATGCATGCATGCATGCATGCATGCATGC\n";
+
+    #[test]
+    fn test_is_bgzf() {
+        struct TestCase<'a> {
+            name: &'a str,
+            data: Vec<u8>,
+            expected: bool,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should detect BGZF data",
+                data: compress(DATA, 10),
+                expected: true,
+            },
+            TestCase {
+                name: "Should not detect plain text",
+                data: DATA.to_vec(),
+                expected: false,
+            },
+            TestCase {
+                name: "Should not detect gzip data without a BC subfield",
+                data: vec![
+                    0x1f, 0x8b, 0x08, 0x00, 0, 0, 0, 0, 0, 0xff, 0, 0, 0, 0, 0, 0,
+                ],
+                expected: false,
+            },
+        ];
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                is_bgzf(&test_case.data),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_reader_read() {
+        let mut reader = Reader::new(std::io::Cursor::new(compress(DATA, 10)));
+        let mut actual = Vec::new();
+        assert!(reader.read_to_end(&mut actual).is_ok());
+        assert_eq!(DATA.to_vec(), actual, "Should decompress all blocks");
+    }
+
+    #[test]
+    fn test_reader_read_invalid() {
+        let mut data = compress(DATA, 10);
+        data[20] ^= 0xff;
+        let mut reader = Reader::new(std::io::Cursor::new(data));
+        let mut actual = Vec::new();
+        assert!(
+            reader.read_to_end(&mut actual).is_err(),
+            "Should return an error on a corrupt block",
+        );
+    }
+
+    #[test]
+    fn test_reader_seek() {
+        struct TestCase<'a> {
+            name: &'a str,
+            position: SeekFrom,
+            expected: &'a [u8],
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should seek forward across blocks",
+                position: SeekFrom::Start(31),
+                expected: b"GCAT",
+            },
+            TestCase {
+                name: "Should seek backward",
+                position: SeekFrom::Start(2),
+                expected: b"GCAT",
+            },
+            TestCase {
+                name: "Should seek relative to the current position",
+                position: SeekFrom::Current(25),
+                expected: b"GCAT",
+            },
+            TestCase {
+                name: "Should seek relative to the end",
+                position: SeekFrom::End(-3),
+                expected: b"GC\n",
+            },
+        ];
+        let data = compress(DATA, 10);
+        let mut index_reader = Reader::new(std::io::Cursor::new(data.clone()));
+        let index = index_reader.index().unwrap().clone();
+        let mut readers = [
+            Reader::new(std::io::Cursor::new(data.clone())),
+            Reader::with_index(std::io::Cursor::new(data), index),
+        ];
+        for reader in readers.iter_mut() {
+            for test_case in test_cases.iter() {
+                assert!(
+                    reader.seek(test_case.position).is_ok(),
+                    "{}",
+                    test_case.name
+                );
+                let mut actual = vec![0; test_case.expected.len()];
+                assert!(reader.read_exact(&mut actual).is_ok(), "{}", test_case.name);
+                assert_eq!(test_case.expected.to_vec(), actual, "{}", test_case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reader_index() {
+        let data = compress(DATA, 10);
+        let eof_len = compress(b"", 10).len();
+        let mut expected = Vec::new();
+        let mut compressed = 0;
+        for (i, chunk) in DATA.chunks(10).enumerate() {
+            expected.push(GziEntry {
+                compressed,
+                uncompressed: 10 * i as u64,
+            });
+            compressed += (compress(chunk, 10).len() - eof_len) as u64;
+        }
+
+        let mut reader = Reader::new(std::io::Cursor::new(data.clone()));
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+        let read_index = reader.index().unwrap().clone();
+        assert_eq!(
+            expected,
+            read_index.entries(),
+            "Should index each data block once by reading",
+        );
+
+        let mut reader = Reader::new(std::io::Cursor::new(data.clone()));
+        let mut first = [0; 4];
+        reader.read_exact(&mut first).unwrap();
+        let scanned_index = reader.index().unwrap().clone();
+        assert_eq!(
+            expected,
+            scanned_index.entries(),
+            "Should index each data block once by reading and then scanning",
+        );
+
+        let mut reader = Reader::new(std::io::Cursor::new(data));
+        assert_eq!(
+            Ok(62),
+            reader.seek(SeekFrom::End(0)).map_err(Error::from),
+            "Should find the end of the data",
+        );
+        let mut gzi = Vec::new();
+        reader.index().unwrap().write(&mut gzi).unwrap();
+        let mut expected_gzi = 6u64.to_le_bytes().to_vec();
+        for entry in &expected[1..] {
+            expected_gzi.extend_from_slice(&entry.compressed.to_le_bytes());
+            expected_gzi.extend_from_slice(&entry.uncompressed.to_le_bytes());
+        }
+        assert_eq!(
+            expected_gzi, gzi,
+            "Should write the blocks after the first, as bgzip does",
+        );
+    }
+}
@@ -1,21 +1,8 @@
 use crate::errors::{Error, ErrorKind, Result};
 
-const SPACE: char = ' ';
+const SPACE: u8 = b' ';
 const NEWLINE: u8 = b'\n';
 
-/// Parse sequence name from a line
-///
-/// A sequence name is defined as the part of a line up to the first space.
-///
-pub fn parse_sequence_name(line: &str) -> String {
-    line.trim()
-        .split(SPACE)
-        .take(1)
-        .collect::<String>()
-        .trim()
-        .into()
-}
-
 /// Count the number of bases in a line
 ///
 /// The number of bases in a line is defined as any byte that isn't a leading or trailing white
@@ -25,60 +12,242 @@ pub fn count_bases(line: &[u8]) -> Result<usize> {
     Ok(std::str::from_utf8(line)?.trim().len())
 }
 
+/// Position of the lines read from an input
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Position {
+    /// Number of lines read, which is the 1-based number of the last line read
+    pub line: usize,
+    /// Byte offset of the start of the last line read
+    pub offset: u64,
+    /// Byte offset just past the last line read
+    pub end: u64,
+}
+
+impl Position {
+    /// Add the location of the next line to an error
+    pub fn locate_next(&self, error: Error) -> Error {
+        error.with_line(self.line + 1).with_offset(self.end)
+    }
+
+    /// Add the location of the last line read to an error
+    pub fn locate(&self, error: Error) -> Error {
+        error.with_line(self.line).with_offset(self.offset)
+    }
+}
+
 /// Read a line of data
 ///
 /// Read a line of data into `buffer` and return the number of bytes read.  An end of file error
-/// is returned if the number of bytes read is 0.
+/// is returned if the number of bytes read is 0.  `position` is advanced past the line, and
+/// errors are located at the line being read.
 ///
-pub fn read_line<B>(reader: &mut B, buffer: &mut Vec<u8>) -> Result<usize>
+pub fn read_line<B>(reader: &mut B, buffer: &mut Vec<u8>, position: &mut Position) -> Result<usize>
 where
     B: std::io::BufRead,
 {
-    let num_bytes = reader.read_until(NEWLINE, buffer)?;
+    let num_bytes = reader
+        .read_until(NEWLINE, buffer)
+        .map_err(|e| position.locate_next(e.into()))?;
     if num_bytes == 0 {
-        return Err(Error::new(ErrorKind::Eof, "end of file"));
+        return Err(position.locate_next(Error::new(ErrorKind::Eof, "end of file")));
     }
+    position.line += 1;
+    position.offset = position.end;
+    position.end += num_bytes as u64;
     Ok(num_bytes)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Find a line of data in memory
+///
+/// Like [`read_line`], but the line starting at `position.end` is returned as a range of `data`
+/// instead of being copied.
+///
+pub fn slice_line(data: &[u8], position: &mut Position) -> Result<std::ops::Range<usize>> {
+    let start = position.end as usize;
+    let rest = data.get(start..).unwrap_or_default();
+    if rest.is_empty() {
+        return Err(position.locate_next(Error::new(ErrorKind::Eof, "end of file")));
+    }
+    let num_bytes = match memchr::memchr(NEWLINE, rest) {
+        Some(i) => i + 1,
+        None => rest.len(),
+    };
+    position.line += 1;
+    position.offset = position.end;
+    position.end += num_bytes as u64;
+    Ok(start..start + num_bytes)
+}
 
-    #[test]
-    fn test_parse_sequence_name() {
-        struct TestCase<'a> {
-            name: &'a str,
-            line: &'a str,
-            expected: &'a str,
+/// LineReader reads lines one at a time, tracking their position
+///
+/// The last line read stays available until the next one is read, so parsers can look at a line
+/// before deciding which record it belongs to.
+///
+pub struct LineReader<R>
+where
+    R: std::io::BufRead,
+{
+    reader: R,
+    line: Vec<u8>,
+    position: Position,
+}
+
+impl<R> LineReader<R>
+where
+    R: std::io::BufRead,
+{
+    /// Construct a line reader from `std::io::BufRead`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: Vec::new(),
+            position: Position::default(),
         }
-        let test_cases = [
-            TestCase {
-                name: "Should parse a name from a line",
-                line: "name description a b c",
-                expected: "name",
-            },
-            TestCase {
-                name: "Should remove trailing new line",
-                line: "name\n",
-                expected: "name",
-            },
-            TestCase {
-                name: "Should remove leading spaces",
-                line: "  abc",
-                expected: "abc",
-            },
-        ];
-        for test_case in test_cases {
-            assert_eq!(
-                String::from(test_case.expected),
-                parse_sequence_name(test_case.line),
-                "{}",
-                test_case.name
-            );
+    }
+
+    /// Read the next line, returning `false` at the end of the input
+    pub fn next_line(&mut self) -> Result<bool> {
+        self.line.clear();
+        match read_line(&mut self.reader, &mut self.line, &mut self.position) {
+            Ok(_) => Ok(true),
+            Err(e) if e.kind == ErrorKind::Eof => Ok(false),
+            Err(e) => Err(e),
         }
     }
 
+    /// Last line read, including its terminator
+    pub fn line(&self) -> &[u8] {
+        &self.line
+    }
+
+    /// Last line read, without leading or trailing whitespace
+    pub fn trimmed(&self) -> &[u8] {
+        let start = self
+            .line
+            .iter()
+            .position(|byte| !byte.is_ascii_whitespace())
+            .unwrap_or(self.line.len());
+        let end = self
+            .line
+            .iter()
+            .rposition(|byte| !byte.is_ascii_whitespace())
+            .map_or(start, |i| i + 1);
+        &self.line[start..end]
+    }
+
+    /// Position of the last line read
+    pub fn position(&self) -> &Position {
+        &self.position
+    }
+
+    /// Consume the line reader and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// Write a description line made of a prefix, a name and an optional description
+pub fn write_description<W>(
+    writer: &mut W,
+    prefix: &[u8],
+    name: &str,
+    description: Option<&str>,
+) -> Result<()>
+where
+    W: std::io::Write,
+{
+    writer.write_all(prefix)?;
+    writer.write_all(name.as_bytes())?;
+    if let Some(description) = description {
+        writer.write_all(&[SPACE])?;
+        writer.write_all(description.as_bytes())?;
+    }
+    writer.write_all(&[NEWLINE])?;
+    Ok(())
+}
+
+/// Copy data from `reader` to `writer`, wrapping it into lines
+///
+/// A newline is written after every `line_width` bytes and after the last line.  A
+/// `line_width` of 0 writes all the data on a single line.  Nothing is written for empty data.
+/// The number of data bytes copied, excluding newlines, is returned.
+///
+pub fn write_wrapped<R, W>(writer: &mut W, mut reader: R, line_width: usize) -> Result<u64>
+where
+    R: std::io::Read,
+    W: std::io::Write,
+{
+    let line_width = match line_width {
+        0 => u64::MAX,
+        line_width => line_width as u64,
+    };
+    let mut total = 0;
+    loop {
+        let num_bytes = std::io::copy(&mut std::io::Read::take(&mut reader, line_width), writer)?;
+        if num_bytes == 0 {
+            return Ok(total);
+        }
+        total += num_bytes;
+        writer.write_all(&[NEWLINE])?;
+        if num_bytes < line_width {
+            return Ok(total);
+        }
+    }
+}
+
+/// Counter counts the bytes written to a writer
+pub struct Counter<W>
+where
+    W: std::io::Write,
+{
+    writer: W,
+    count: u64,
+}
+
+impl<W> Counter<W>
+where
+    W: std::io::Write,
+{
+    /// Construct a counter from `std::io::Write`
+    pub fn new(writer: W) -> Self {
+        Self { writer, count: 0 }
+    }
+
+    /// Number of bytes written so far
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Get a mutable reference to the underlying writer
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Consume the counter and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W> std::io::Write for Counter<W>
+where
+    W: std::io::Write,
+{
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let num_bytes = self.writer.write(buf)?;
+        self.count += num_bytes as u64;
+        Ok(num_bytes)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn test_count_bases() {
         struct TestCase<'a> {
@@ -103,19 +272,100 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_write_wrapped() {
+        let mut output = Vec::new();
+        assert_eq!(
+            Ok(7),
+            write_wrapped(&mut output, &b"abcdefg"[..], 3),
+            "Should return the number of bytes copied",
+        );
+        assert_eq!(
+            b"abc\ndef\ng\n".to_vec(),
+            output,
+            "Should wrap data into lines",
+        );
+    }
+
+    #[test]
+    fn test_line_reader() {
+        let mut reader = LineReader::new(&b"abc\r\n\ndef"[..]);
+        assert_eq!(Ok(true), reader.next_line(), "Should read a line");
+        assert_eq!(b"abc\r\n", reader.line(), "Should keep the line terminator");
+        assert_eq!(b"abc", reader.trimmed(), "Should trim the line");
+        assert_eq!(Ok(true), reader.next_line(), "Should read a blank line");
+        assert_eq!(Ok(true), reader.next_line(), "Should read the last line");
+        assert_eq!(3, reader.position().line, "Should count lines");
+        assert_eq!(
+            Ok(false),
+            reader.next_line(),
+            "Should return false at the end"
+        );
+        assert!(reader.line().is_empty(), "Should clear the line at the end");
+    }
+
     #[test]
     fn test_read_line() {
-        let mut input: &[u8] = b"abcdefg";
+        let mut input: &[u8] = b"abc\ndefg";
         let mut buffer = Vec::new();
+        let mut position = Position::default();
         assert_eq!(
-            Ok(7),
-            read_line(&mut input, &mut buffer),
+            Ok(4),
+            read_line(&mut input, &mut buffer, &mut position),
             "Should read the correct number of bytes",
         );
         assert_eq!(
-            ErrorKind::Eof,
-            read_line(&mut input, &mut buffer).unwrap_err().kind,
-            "Should return an Eof error",
+            Ok(4),
+            read_line(&mut input, &mut buffer, &mut position),
+            "Should read the last line without a newline",
+        );
+        assert_eq!(
+            Position {
+                line: 2,
+                offset: 4,
+                end: 8,
+            },
+            position,
+            "Should track the position of the last line",
+        );
+        let error = read_line(&mut input, &mut buffer, &mut position).unwrap_err();
+        assert_eq!(ErrorKind::Eof, error.kind, "Should return an Eof error");
+        assert_eq!(
+            (Some(3), Some(8)),
+            (error.location.line, error.location.offset),
+            "Should locate errors at the next line",
+        );
+    }
+
+    #[test]
+    fn test_slice_line() {
+        let input: &[u8] = b"abc\ndefg";
+        let mut position = Position::default();
+        assert_eq!(
+            Ok(0..4),
+            slice_line(input, &mut position),
+            "Should find the first line",
+        );
+        assert_eq!(
+            Ok(4..8),
+            slice_line(input, &mut position),
+            "Should find the last line without a newline",
+        );
+        assert_eq!(
+            Position {
+                line: 2,
+                offset: 4,
+                end: 8,
+            },
+            position,
+            "Should track the position of the last line",
+        );
+        let error = slice_line(input, &mut position).unwrap_err();
+        assert_eq!(ErrorKind::Eof, error.kind, "Should return an Eof error");
+        assert_eq!(
+            (Some(3), Some(8)),
+            (error.location.line, error.location.offset),
+            "Should locate errors at the next line",
         );
     }
 }
@@ -0,0 +1,133 @@
+use super::bgzf;
+use crate::errors::{Error, ErrorKind, Result};
+
+/// Number of leading bytes needed to detect the compression format
+pub const SNIFF_LEN: usize = 18;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const BZIP2_MAGIC: &[u8] = b"BZh";
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Compression format of an input file
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Compression {
+    /// Uncompressed data
+    None,
+    /// Blocked gzip, which supports random access
+    Bgzf,
+    /// Plain gzip without BGZF blocks
+    Gzip,
+    /// bzip2
+    Bzip2,
+    /// xz
+    Xz,
+}
+
+impl Compression {
+    /// Detect the compression format from the leading bytes of a file
+    ///
+    /// At least [`SNIFF_LEN`] bytes should be given, unless the file is shorter.
+    ///
+    pub fn detect(header: &[u8]) -> Self {
+        if bgzf::is_bgzf(header) {
+            Self::Bgzf
+        } else if header.starts_with(GZIP_MAGIC) {
+            Self::Gzip
+        } else if header.starts_with(BZIP2_MAGIC) {
+            Self::Bzip2
+        } else if header.starts_with(XZ_MAGIC) {
+            Self::Xz
+        } else {
+            Self::None
+        }
+    }
+
+    /// Check that the format can be read with random access
+    ///
+    /// A [`ErrorKind::Compression`] error explaining how to fix the input is returned for formats
+    /// that can't be indexed.
+    ///
+    pub fn check_random_access(&self) -> Result<()> {
+        let name = match self {
+            Self::None | Self::Bgzf => return Ok(()),
+            Self::Gzip => "gzip compressed but not BGZF",
+            Self::Bzip2 => "bzip2 compressed",
+            Self::Xz => "xz compressed",
+        };
+        Err(Error::new(
+            ErrorKind::Compression,
+            &format!(
+                "input is {}, which does not support random access; recompress with bgzip",
+                name
+            ),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compression_detect() {
+        struct TestCase<'a> {
+            name: &'a str,
+            header: Vec<u8>,
+            expected: Compression,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should detect plain text",
+                header: b">chr1\nACGT\n".to_vec(),
+                expected: Compression::None,
+            },
+            TestCase {
+                name: "Should detect an empty file as plain text",
+                header: vec![],
+                expected: Compression::None,
+            },
+            TestCase {
+                name: "Should detect BGZF",
+                header: bgzf::compress(b">chr1\nACGT\n", 4),
+                expected: Compression::Bgzf,
+            },
+            TestCase {
+                name: "Should detect plain gzip",
+                header: vec![0x1f, 0x8b, 0x08, 0x08, 0, 0, 0, 0, 0, 0x03, b'a', 0],
+                expected: Compression::Gzip,
+            },
+            TestCase {
+                name: "Should detect bzip2",
+                header: b"BZh91AY&SY".to_vec(),
+                expected: Compression::Bzip2,
+            },
+            TestCase {
+                name: "Should detect xz",
+                header: vec![0xfd, b'7', b'z', b'X', b'Z', 0x00, 0x00, 0x04],
+                expected: Compression::Xz,
+            },
+        ];
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                Compression::detect(&test_case.header),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_compression_check_random_access() {
+        assert!(Compression::None.check_random_access().is_ok());
+        assert!(Compression::Bgzf.check_random_access().is_ok());
+        for compression in [Compression::Gzip, Compression::Bzip2, Compression::Xz] {
+            assert_eq!(
+                ErrorKind::Compression,
+                compression.check_random_access().unwrap_err().kind,
+                "Should reject {:?}",
+                compression,
+            );
+        }
+    }
+}
@@ -0,0 +1,31 @@
+mod writer;
+
+pub use writer::Writer;
+
+/// Sequence dictionary record, written as a SAM `@SQ` header line as defined in the
+/// [`documentation`]
+///
+/// [`documentation`]: https://samtools.github.io/hts-specs/SAMv1.pdf
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Record {
+    /// Reference sequence name (`SN`)
+    pub name: String,
+    /// Reference sequence length (`LN`)
+    pub length: usize,
+    /// MD5 checksum of the normalized sequence (`M5`)
+    pub md5: Option<String>,
+    /// Genome assembly identifier (`AS`)
+    pub assembly: Option<String>,
+    /// Species (`SP`)
+    pub species: Option<String>,
+    /// URI of the sequence (`UR`)
+    pub uri: Option<String>,
+}
+
+impl Record {
+    /// Construct a default dictionary record
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
@@ -0,0 +1,108 @@
+use super::Record;
+use crate::errors::Result;
+
+const HEADER: &str = "@HD\tVN:1.0\tSO:unsorted";
+
+/// Writer is a writer for sequence dictionaries
+pub struct Writer<W: std::io::Write> {
+    writer: W,
+}
+
+impl<W> Writer<W>
+where
+    W: std::io::Write,
+{
+    /// Construct a dictionary writer from `std::io::Write`
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Write the `@HD` header line
+    pub fn write_header(&mut self) -> Result<()> {
+        writeln!(self.writer, "{}", HEADER)?;
+        Ok(())
+    }
+
+    /// Write a dictionary record as an `@SQ` line
+    pub fn write(&mut self, record: &Record) -> Result<()> {
+        write!(self.writer, "@SQ\tSN:{}\tLN:{}", record.name, record.length)?;
+        let tags = [
+            ("M5", &record.md5),
+            ("AS", &record.assembly),
+            ("SP", &record.species),
+            ("UR", &record.uri),
+        ];
+        for (tag, value) in tags {
+            if let Some(value) = value {
+                write!(self.writer, "\t{}:{}", tag, value)?;
+            }
+        }
+        writeln!(self.writer)?;
+        Ok(())
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writer_write() {
+        struct TestCase<'a> {
+            name: &'a str,
+            record: Record,
+            expected: &'a str,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should write the required tags",
+                record: Record {
+                    name: "chr1".into(),
+                    length: 10,
+                    ..Record::new()
+                },
+                expected: "@SQ\tSN:chr1\tLN:10\n",
+            },
+            TestCase {
+                name: "Should write optional tags",
+                record: Record {
+                    name: "chr1".into(),
+                    length: 10,
+                    md5: Some("abc".into()),
+                    assembly: Some("GRCh38".into()),
+                    species: Some("Homo sapiens".into()),
+                    uri: Some("file:///ref.fa".into()),
+                },
+                expected:
+                    "@SQ\tSN:chr1\tLN:10\tM5:abc\tAS:GRCh38\tSP:Homo sapiens\tUR:file:///ref.fa\n",
+            },
+        ];
+        for test_case in test_cases {
+            let mut writer = Writer::new(vec![]);
+            assert!(
+                writer.write(&test_case.record).is_ok(),
+                "{}",
+                test_case.name
+            );
+            let data = String::from_utf8(writer.writer).unwrap();
+            assert_eq!(test_case.expected, &data, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_writer_write_header() {
+        let mut writer = Writer::new(vec![]);
+        assert!(writer.write_header().is_ok());
+        assert_eq!(
+            "@HD\tVN:1.0\tSO:unsorted\n",
+            String::from_utf8(writer.writer).unwrap(),
+            "Should write the header line",
+        );
+    }
+}
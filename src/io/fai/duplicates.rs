@@ -0,0 +1,282 @@
+use super::Record;
+use crate::errors::{Error, ErrorKind, Result};
+use std::collections::{HashMap, HashSet};
+
+const ERROR: &str = "error";
+const SKIP: &str = "skip";
+const KEEP_FIRST: &str = "keep-first";
+const RENAME: &str = "rename";
+const RENAME_SEPARATOR: char = '_';
+
+/// Duplicates is the policy for Fai records that share a sequence name
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Duplicates {
+    /// Fail on the first duplicate name, like samtools
+    #[default]
+    Error,
+    /// Drop every record with a duplicated name, since none of them can be fetched unambiguously
+    Skip,
+    /// Keep the first record with a name and drop the later ones
+    KeepFirst,
+    /// Keep every record, renaming later ones to `<name>_<n>`
+    Rename,
+}
+
+impl std::str::FromStr for Duplicates {
+    type Err = Error;
+
+    /// Parse a policy from `error`, `skip`, `keep-first` or `rename`
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            ERROR => Ok(Self::Error),
+            SKIP => Ok(Self::Skip),
+            KEEP_FIRST => Ok(Self::KeepFirst),
+            RENAME => Ok(Self::Rename),
+            _ => Err(Error::new(
+                ErrorKind::User,
+                &format!("invalid duplicate policy: {}", s),
+            )),
+        }
+    }
+}
+
+/// DuplicateWarning reports a record whose name was already used
+#[derive(Clone, Debug, PartialEq)]
+pub struct DuplicateWarning {
+    /// Duplicated sequence name
+    pub name: String,
+    /// 0-based position of the record in the input
+    pub record: usize,
+    /// New name of the record, if it was renamed rather than dropped
+    pub renamed: Option<String>,
+}
+
+impl std::fmt::Display for DuplicateWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "duplicate sequence name: {}, record: {}, ",
+            self.name,
+            self.record + 1
+        )?;
+        match &self.renamed {
+            Some(renamed) => write!(f, "renamed to {}", renamed),
+            None => write!(f, "dropped"),
+        }
+    }
+}
+
+/// Apply a duplicate name policy to Fai records
+///
+/// The records that are kept are returned in input order, along with a warning for every
+/// record that was dropped or renamed.
+///
+pub fn deduplicate(
+    records: Vec<Record>,
+    duplicates: Duplicates,
+) -> Result<(Vec<Record>, Vec<DuplicateWarning>)> {
+    let mut counts = HashMap::<String, usize>::new();
+    for record in records.iter() {
+        *counts.entry(record.name.clone()).or_default() += 1;
+    }
+    let mut seen = HashSet::new();
+    let mut kept = Vec::with_capacity(records.len());
+    let mut warnings = Vec::new();
+    for (i, mut record) in records.into_iter().enumerate() {
+        let first = seen.insert(record.name.clone());
+        let warning = DuplicateWarning {
+            name: record.name.clone(),
+            record: i,
+            renamed: None,
+        };
+        match duplicates {
+            _ if counts[&record.name] == 1 => kept.push(record),
+            Duplicates::Error if first => kept.push(record),
+            Duplicates::Error => {
+                return Err(Error::new(ErrorKind::Input, "duplicate sequence name")
+                    .with_offset(record.offset)
+                    .with_record(&record.name));
+            }
+            Duplicates::Skip => warnings.push(warning),
+            Duplicates::KeepFirst if first => kept.push(record),
+            Duplicates::KeepFirst => warnings.push(warning),
+            Duplicates::Rename if first => kept.push(record),
+            Duplicates::Rename => {
+                let renamed = rename(&record.name, &counts, &seen);
+                seen.insert(renamed.clone());
+                record.name = renamed.clone();
+                kept.push(record);
+                warnings.push(DuplicateWarning {
+                    renamed: Some(renamed),
+                    ..warning
+                });
+            }
+        }
+    }
+    Ok((kept, warnings))
+}
+
+/// Find the first `<name>_<n>` that is not an existing or already assigned name
+fn rename(name: &str, names: &HashMap<String, usize>, assigned: &HashSet<String>) -> String {
+    (2..)
+        .map(|n| format!("{}{}{}", name, RENAME_SEPARATOR, n))
+        .find(|renamed| !names.contains_key(renamed) && !assigned.contains(renamed))
+        .unwrap()
+}
+
+/// Get the name a [`Duplicates::Rename`] record was renamed from
+///
+/// The .fai only stores the new `<name>_<n>` name, so `<name>` is recovered from it.  `None` is
+/// returned if `name` could not have been produced by renaming.
+///
+pub(super) fn renamed_from(name: &str) -> Option<&str> {
+    let (original, n) = name.rsplit_once(RENAME_SEPARATOR)?;
+    match n.parse::<usize>() {
+        // Only the exact form written by rename, without a sign or leading zeros
+        Ok(number) if number >= 2 && number.to_string() == n => Some(original),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(names: &[&str]) -> Vec<Record> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| Record {
+                name: name.to_string(),
+                offset: i as u64,
+                ..Record::new()
+            })
+            .collect()
+    }
+
+    fn names(records: &[Record]) -> Vec<&str> {
+        records.iter().map(|record| record.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_deduplicate() {
+        struct TestCase<'a> {
+            name: &'a str,
+            duplicates: Duplicates,
+            expected: Vec<&'a str>,
+            expected_warnings: usize,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should drop every record with a duplicated name",
+                duplicates: Duplicates::Skip,
+                expected: vec!["b", "a_2"],
+                expected_warnings: 3,
+            },
+            TestCase {
+                name: "Should keep the first record with a name",
+                duplicates: Duplicates::KeepFirst,
+                expected: vec!["a", "b", "a_2"],
+                expected_warnings: 2,
+            },
+            TestCase {
+                name: "Should rename later records without clashing with existing names",
+                duplicates: Duplicates::Rename,
+                expected: vec!["a", "b", "a_3", "a_2", "a_4"],
+                expected_warnings: 2,
+            },
+        ];
+        for test_case in test_cases {
+            let actual = deduplicate(records(&["a", "b", "a", "a_2", "a"]), test_case.duplicates);
+            assert!(actual.is_ok(), "{}", test_case.name);
+            let (records, warnings) = actual.unwrap();
+            assert_eq!(test_case.expected, names(&records), "{}", test_case.name);
+            assert_eq!(
+                test_case.expected_warnings,
+                warnings.len(),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_deduplicate_error() {
+        let actual = deduplicate(records(&["a", "b", "a"]), Duplicates::Error);
+        assert_eq!(
+            Err(Error::new(ErrorKind::Input, "duplicate sequence name")
+                .with_offset(2)
+                .with_record("a")),
+            actual,
+            "Should return an error on a duplicate name",
+        );
+        assert!(
+            deduplicate(records(&["a", "b"]), Duplicates::Error).is_ok(),
+            "Should accept unique names",
+        );
+    }
+
+    #[test]
+    fn test_duplicate_warning_display() {
+        let warning = DuplicateWarning {
+            name: "chr1".into(),
+            record: 2,
+            renamed: Some("chr1_2".into()),
+        };
+        assert_eq!(
+            "duplicate sequence name: chr1, record: 3, renamed to chr1_2",
+            warning.to_string(),
+            "Should describe the warning",
+        );
+    }
+
+    #[test]
+    fn test_renamed_from() {
+        struct TestCase<'a> {
+            name: &'a str,
+            renamed: &'a str,
+            expected: Option<&'a str>,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should strip the rename suffix",
+                renamed: "chr1_2",
+                expected: Some("chr1"),
+            },
+            TestCase {
+                name: "Should only strip the last suffix",
+                renamed: "chr1_2_10",
+                expected: Some("chr1_2"),
+            },
+            TestCase {
+                name: "Should reject a name without a suffix",
+                renamed: "chr1",
+                expected: None,
+            },
+            TestCase {
+                name: "Should reject a suffix rename does not produce",
+                renamed: "chr1_1",
+                expected: None,
+            },
+            TestCase {
+                name: "Should reject a suffix with leading zeros",
+                renamed: "chr1_02",
+                expected: None,
+            },
+        ];
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                renamed_from(test_case.renamed),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_duplicates_from_str() {
+        assert_eq!(Ok(Duplicates::KeepFirst), "keep-first".parse());
+        assert!("first".parse::<Duplicates>().is_err());
+    }
+}
@@ -0,0 +1,828 @@
+use super::super::sequence::NameSplit;
+use super::super::source::{Access, Source};
+use super::duplicates::renamed_from;
+use super::{Reader, Record};
+use crate::errors::{Error, ErrorKind, Result};
+use std::collections::HashMap;
+use std::io::{Read, Seek};
+
+const NEWLINE: u8 = b'\n';
+const CARRIAGE_RETURN: u8 = b'\r';
+const FASTA_PREFIX: u8 = b'>';
+const FASTQ_PREFIX: u8 = b'@';
+const QUALITY_PREFIX: u8 = b'+';
+const CHUNK_LEN: u64 = 256;
+
+/// IndexedReader retrieves sequence from an indexed FASTA/FASTQ file
+///
+/// Positions are 0-based and intervals are half open, so `fetch(name, 0, 10)` returns the first
+/// 10 bases of `name`.
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::fai;
+/// use std::io::Read;
+///
+/// let fasta: &[u8] = b">chr1\nACGTACGT\nACGT\n";
+/// let index: &[u8] = b"chr1\t12\t6\t8\t9\n";
+/// let records = fai::Reader::new(index)
+///     .iter()
+///     .collect::<rust_samtools::errors::Result<Vec<_>>>()?;
+/// let mut reader = fai::IndexedReader::new(std::io::Cursor::new(fasta), records);
+/// assert_eq!(b"GTACGTAC".to_vec(), reader.fetch("chr1", 2, 10)?);
+///
+/// let mut bases = String::new();
+/// reader.fetch_reader("chr1", 6, 12)?.read_to_string(&mut bases)?;
+/// assert_eq!("GTACGT", bases);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
+pub struct IndexedReader<R>
+where
+    R: Read + Seek,
+{
+    reader: R,
+    records: Vec<Record>,
+    names: HashMap<String, usize>,
+    name_split: NameSplit,
+}
+
+impl<R> IndexedReader<R>
+where
+    R: Read + Seek,
+{
+    /// Construct an indexed reader from a FASTA/FASTQ source and its Fai records
+    ///
+    /// If several records share a name, the first one is used.
+    ///
+    pub fn new(reader: R, records: Vec<Record>) -> Self {
+        let mut names = HashMap::new();
+        for (i, record) in records.iter().enumerate() {
+            names.entry(record.name.clone()).or_insert(i);
+        }
+        Self {
+            reader,
+            records,
+            names,
+            name_split: NameSplit::default(),
+        }
+    }
+
+    /// Set the rule that was used to split sequence names from description lines when indexing
+    ///
+    /// It is used by [`IndexedReader::validate`] to recognize header lines.
+    ///
+    pub fn with_name_split(mut self, name_split: NameSplit) -> Self {
+        self.name_split = name_split;
+        self
+    }
+
+    /// Fai records in index order
+    pub fn records(&self) -> &[Record] {
+        &self.records
+    }
+
+    /// Look up the Fai record of a sequence
+    pub fn record(&self, name: &str) -> Option<&Record> {
+        self.names.get(name).map(|&i| &self.records[i])
+    }
+
+    /// Fetch the bases of the interval `[start, end)` of a sequence
+    pub fn fetch(&mut self, name: &str, start: usize, end: usize) -> Result<Vec<u8>> {
+        let mut sequence = Vec::with_capacity(end.saturating_sub(start));
+        self.fetch_reader(name, start, end)?
+            .read_to_end(&mut sequence)?;
+        Ok(sequence)
+    }
+
+    /// Stream the bases of the interval `[start, end)` of a sequence
+    ///
+    /// The returned reader yields bases only, with line terminators removed, and never holds more
+    /// than the caller's buffer in memory.
+    ///
+    pub fn fetch_reader(&mut self, name: &str, start: usize, end: usize) -> Result<Fetch<'_, R>> {
+        let record = self.interval_record(name, start, end)?;
+        let offset = record.base_offset(start);
+        self.open(name, offset, start, end)
+    }
+
+    /// Fetch the qualities of the interval `[start, end)` of a FASTQ sequence
+    pub fn fetch_quality(&mut self, name: &str, start: usize, end: usize) -> Result<Vec<u8>> {
+        let mut quality = Vec::with_capacity(end.saturating_sub(start));
+        self.fetch_quality_reader(name, start, end)?
+            .read_to_end(&mut quality)?;
+        Ok(quality)
+    }
+
+    /// Stream the qualities of the interval `[start, end)` of a FASTQ sequence
+    ///
+    /// An error is returned if the sequence was indexed as FASTA.
+    ///
+    pub fn fetch_quality_reader(
+        &mut self,
+        name: &str,
+        start: usize,
+        end: usize,
+    ) -> Result<Fetch<'_, R>> {
+        let record = self.interval_record(name, start, end)?;
+        let offset = record.quality_offset(start).ok_or_else(|| {
+            Error::new(
+                ErrorKind::User,
+                &format!("sequence {} has no qualities", name),
+            )
+        })?;
+        self.open(name, offset, start, end)
+    }
+
+    /// Check a sample of the Fai records against the input
+    ///
+    /// Up to `num_samples` evenly spaced records, including the first and last, are checked
+    /// without reading whole sequences: each sequence must follow a header line whose name, split
+    /// with the reader's [`NameSplit`], is the record name, or the name it was renamed from by
+    /// [`Duplicates::Rename`](super::Duplicates::Rename), and its last
+    /// base and quality must end a line.  An Input error naming the first mismatching record is
+    /// returned, which usually means the input changed after it was indexed.
+    ///
+    pub fn validate(&mut self, num_samples: usize) -> Result<()> {
+        for i in sample(self.records.len(), num_samples) {
+            let record = self.records[i].clone();
+            if let Some(problem) = self.check_record(&record)? {
+                return Err(Error::new(
+                    ErrorKind::Input,
+                    &format!("index does not match input: {}", problem),
+                )
+                .with_record(&record.name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check a record against the input, returning a description of any mismatch
+    fn check_record(&mut self, record: &Record) -> Result<Option<&'static str>> {
+        let prefix = match record.qual_offset {
+            Some(_) => FASTQ_PREFIX,
+            None => FASTA_PREFIX,
+        };
+        match self.line_before(record.offset)? {
+            Some(line) if self.is_record_header(&line, prefix, &record.name) => {}
+            _ => return Ok(Some("sequence does not follow its header line")),
+        }
+        if record.length > 0 && !self.ends_line(record.base_offset(record.length - 1))? {
+            return Ok(Some("last base does not end a line"));
+        }
+        if let Some(qual_offset) = record.qual_offset {
+            match self.line_before(qual_offset)? {
+                Some(line) if line.first() == Some(&QUALITY_PREFIX) => {}
+                _ => return Ok(Some("quality does not follow a separator line")),
+            }
+        }
+        match record.quality_offset(record.length.saturating_sub(1)) {
+            Some(offset) if record.length > 0 && !self.ends_line(offset)? => {
+                Ok(Some("last quality does not end a line"))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Check whether `line` is the header line of the record named `name`
+    ///
+    /// A record renamed by [`Duplicates::Rename`](super::Duplicates::Rename) follows the header
+    /// of the name it was renamed from, which the first record with that name keeps.
+    ///
+    fn is_record_header(&self, line: &[u8], prefix: u8, name: &str) -> bool {
+        if is_header(line, prefix, name, self.name_split) {
+            return true;
+        }
+        match renamed_from(name) {
+            Some(original) => {
+                self.names.contains_key(original)
+                    && is_header(line, prefix, original, self.name_split)
+            }
+            None => false,
+        }
+    }
+
+    /// Check that the byte at `offset` is not whitespace and is the last one on its line
+    fn ends_line(&mut self, offset: u64) -> Result<bool> {
+        match (self.byte_at(offset)?, self.byte_at(offset + 1)?) {
+            (Some(byte), next) if !byte.is_ascii_whitespace() => {
+                Ok(matches!(next, None | Some(NEWLINE) | Some(CARRIAGE_RETURN)))
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Read the line that ends just before `offset`, without its terminator
+    ///
+    /// `None` is returned if `offset` is not at the start of a line.
+    ///
+    fn line_before(&mut self, offset: u64) -> Result<Option<Vec<u8>>> {
+        if offset == 0 || self.byte_at(offset - 1)? != Some(NEWLINE) {
+            return Ok(None);
+        }
+        let mut end = offset - 1;
+        let mut line = Vec::new();
+        loop {
+            let start = end.saturating_sub(CHUNK_LEN);
+            let mut chunk = vec![0; (end - start) as usize];
+            self.reader.seek(std::io::SeekFrom::Start(start))?;
+            self.reader.read_exact(&mut chunk)?;
+            let line_start = chunk.iter().rposition(|&byte| byte == NEWLINE);
+            chunk.drain(..line_start.map_or(0, |i| i + 1));
+            chunk.append(&mut line);
+            line = chunk;
+            if line_start.is_some() || start == 0 {
+                break;
+            }
+            end = start;
+        }
+        if line.last() == Some(&CARRIAGE_RETURN) {
+            line.pop();
+        }
+        Ok(Some(line))
+    }
+
+    /// Read the byte at `offset`, or `None` past the end of the input
+    fn byte_at(&mut self, offset: u64) -> Result<Option<u8>> {
+        self.reader.seek(std::io::SeekFrom::Start(offset))?;
+        let mut byte = [0];
+        match self.reader.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    }
+
+    /// Look up a record and check that `[start, end)` lies within it
+    fn interval_record(&self, name: &str, start: usize, end: usize) -> Result<&Record> {
+        let record = self.record(name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::User,
+                &format!("sequence {} not found in index", name),
+            )
+        })?;
+        if start > end || end > record.length {
+            return Err(Error::new(
+                ErrorKind::User,
+                &format!(
+                    "invalid interval {}-{} for sequence {} of length {}",
+                    start, end, name, record.length
+                ),
+            ));
+        }
+        Ok(record)
+    }
+
+    /// Seek to `offset` and stream the interval `[start, end)` laid out as the record's lines
+    fn open(&mut self, name: &str, offset: u64, start: usize, end: usize) -> Result<Fetch<'_, R>> {
+        let record = &self.records[self.names[name]];
+        let line_remaining = match record.line_bases {
+            0 => 0,
+            line_bases => line_bases - start % line_bases,
+        };
+        let terminator = record
+            .line_width
+            .checked_sub(record.line_bases)
+            .ok_or_else(|| line_width_error(record))?;
+        let line_bases = record.line_bases;
+        self.reader.seek(std::io::SeekFrom::Start(offset))?;
+        Ok(Fetch {
+            reader: &mut self.reader,
+            remaining: end - start,
+            line_remaining,
+            terminator,
+            line_bases,
+        })
+    }
+}
+
+impl IndexedReader<Source> {
+    /// Construct an indexed reader from a FASTA/FASTQ path and the path of its Fai index
+    ///
+    /// BGZF compressed input is supported, using its `.gzi` index if there is one.
+    ///
+    pub fn from_path<P, Q>(path: P, index_path: Q) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+        Q: AsRef<std::path::Path>,
+    {
+        Self::from_path_with(path, index_path, Access::default())
+    }
+
+    /// Construct an indexed reader from paths, reading uncompressed input with `access`
+    ///
+    /// An Input error is returned for records whose line width is less than their line bases.
+    ///
+    pub fn from_path_with<P, Q>(path: P, index_path: Q, access: Access) -> Result<Self>
+    where
+        P: AsRef<std::path::Path>,
+        Q: AsRef<std::path::Path>,
+    {
+        let records = Reader::from_path(&index_path)?
+            .iter()
+            .collect::<Result<Vec<_>>>()?;
+        if let Some(record) = records
+            .iter()
+            .find(|record| record.line_width < record.line_bases)
+        {
+            return Err(line_width_error(record).with_path(index_path));
+        }
+        Ok(Self::new(Source::from_path_with(path, access)?, records))
+    }
+}
+
+/// Error for a Fai record whose line width is less than its line bases
+fn line_width_error(record: &Record) -> Error {
+    Error::new(
+        ErrorKind::Input,
+        &format!(
+            "invalid fai record: line width {} is less than line bases {}",
+            record.line_width, record.line_bases
+        ),
+    )
+    .with_record(&record.name)
+}
+
+/// Check whether `line` is a header line for sequence `name`
+///
+/// The line must hold exactly `name` when split with `name_split`, as the indexer splits it, so
+/// a header renamed from `>chr1` to `>chr1_alt` does not match `chr1`.
+///
+fn is_header(line: &[u8], prefix: u8, name: &str, name_split: NameSplit) -> bool {
+    match line.split_first() {
+        Some((&first, rest)) if first == prefix => {
+            matches!(std::str::from_utf8(rest), Ok(rest) if name_split.split(rest).0 == name)
+        }
+        _ => false,
+    }
+}
+
+/// Pick up to `num_samples` evenly spaced indices out of `len`, including the first and last
+fn sample(len: usize, num_samples: usize) -> Vec<usize> {
+    match num_samples {
+        n if n >= len => (0..len).collect(),
+        0 | 1 => (0..num_samples).collect(),
+        n => (0..n).map(|i| i * (len - 1) / (n - 1)).collect(),
+    }
+}
+
+/// Fetch streams the bases of a sequence interval
+///
+/// Created by [`IndexedReader::fetch_reader`].
+///
+pub struct Fetch<'a, R>
+where
+    R: Read,
+{
+    reader: &'a mut R,
+    remaining: usize,
+    line_remaining: usize,
+    terminator: usize,
+    line_bases: usize,
+}
+
+impl<'a, R> Read for Fetch<'a, R>
+where
+    R: Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.remaining == 0 || buf.is_empty() {
+            return Ok(0);
+        }
+        if self.line_remaining == 0 {
+            let mut terminator = (&mut *self.reader).take(self.terminator as u64);
+            std::io::copy(&mut terminator, &mut std::io::sink())?;
+            self.line_remaining = self.line_bases;
+        }
+        let len = buf.len().min(self.remaining).min(self.line_remaining);
+        let num_bytes = self.reader.read(&mut buf[..len])?;
+        if num_bytes == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= num_bytes;
+        self.line_remaining -= num_bytes;
+        Ok(num_bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn example() -> IndexedReader<std::io::Cursor<&'static [u8]>> {
+        // Test example from samtools documentation here:
+        //   https://www.htslib.org/doc/faidx.html
+        let input: &[u8] = br#">one
+ATGCATGCATGCATGCATGCATGCATGCAT
+GCATGCATGCATGCATGCATGCATGCATGC
+ATGCAT
+>two another chromosome
+ATGCATGCATGCAT
+GCATGCATGCATGC
+"#;
+        let records = vec![
+            Record {
+                name: "one".into(),
+                length: 66,
+                offset: 5,
+                line_bases: 30,
+                line_width: 31,
+                qual_offset: None,
+                md5: None,
+            },
+            Record {
+                name: "two".into(),
+                length: 28,
+                offset: 98,
+                line_bases: 14,
+                line_width: 15,
+                qual_offset: None,
+                md5: None,
+            },
+        ];
+        IndexedReader::new(std::io::Cursor::new(input), records)
+    }
+
+    #[test]
+    fn test_indexed_reader_fetch() {
+        struct TestCase<'a> {
+            name: &'a str,
+            sequence: &'a str,
+            start: usize,
+            end: usize,
+            expect_error: bool,
+            expected: &'a [u8],
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should fetch within a line",
+                sequence: "one",
+                start: 1,
+                end: 5,
+                expect_error: false,
+                expected: b"TGCA",
+            },
+            TestCase {
+                name: "Should fetch across lines",
+                sequence: "one",
+                start: 27,
+                end: 35,
+                expect_error: false,
+                expected: b"CATGCATG",
+            },
+            TestCase {
+                name: "Should fetch a whole sequence",
+                sequence: "two",
+                start: 0,
+                end: 28,
+                expect_error: false,
+                expected: b"ATGCATGCATGCATGCATGCATGCATGC",
+            },
+            TestCase {
+                name: "Should fetch an empty interval",
+                sequence: "two",
+                start: 3,
+                end: 3,
+                expect_error: false,
+                expected: b"",
+            },
+            TestCase {
+                name: "Should return an error for an unknown sequence",
+                sequence: "three",
+                start: 0,
+                end: 1,
+                expect_error: true,
+                expected: b"",
+            },
+            TestCase {
+                name: "Should return an error past the end of a sequence",
+                sequence: "two",
+                start: 0,
+                end: 29,
+                expect_error: true,
+                expected: b"",
+            },
+            TestCase {
+                name: "Should return an error if start is after end",
+                sequence: "one",
+                start: 5,
+                end: 1,
+                expect_error: true,
+                expected: b"",
+            },
+        ];
+        let mut reader = example();
+        for test_case in test_cases {
+            let actual = reader.fetch(test_case.sequence, test_case.start, test_case.end);
+            if test_case.expect_error {
+                assert!(actual.is_err(), "{}", test_case.name);
+            } else {
+                assert_eq!(
+                    Ok(test_case.expected.to_vec()),
+                    actual,
+                    "{}",
+                    test_case.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_indexed_reader_fetch_reader() {
+        let mut reader = example();
+        let mut fetch = reader.fetch_reader("one", 25, 40).unwrap();
+        let mut buffer = [0; 4];
+        let mut actual = Vec::new();
+        loop {
+            let num_bytes = fetch.read(&mut buffer).unwrap();
+            if num_bytes == 0 {
+                break;
+            }
+            assert!(num_bytes <= buffer.len(), "Should not overrun the buffer");
+            actual.extend_from_slice(&buffer[..num_bytes]);
+        }
+        assert_eq!(
+            b"TGCATGCATGCATGC".to_vec(),
+            actual,
+            "Should stream bases across lines in small reads",
+        );
+    }
+
+    #[test]
+    fn test_indexed_reader_fetch_quality() {
+        // Test example from samtools documentation here:
+        //   https://www.htslib.org/doc/faidx.html
+        let input: &[u8] = br#"@fastq1
+ATGCATGCATGCATGCATGCATGCATGCAT
+GCATGCATGCATGCATGCATGCATGCATGC
+ATGCAT
++
+FFFA@@FFFFFFFFFFHHB:::@BFFFFGG
+HIHIIIIIIIIIIIIIIIIIIIIIIIFFFF
+8011<<
+"#;
+        let records = vec![Record {
+            name: "fastq1".into(),
+            length: 66,
+            offset: 8,
+            line_bases: 30,
+            line_width: 31,
+            qual_offset: Some(79),
+            md5: None,
+        }];
+        let mut reader = IndexedReader::new(std::io::Cursor::new(input), records);
+        assert_eq!(
+            Ok(b"GGHIH".to_vec()),
+            reader.fetch_quality("fastq1", 28, 33),
+            "Should fetch qualities across lines",
+        );
+        assert_eq!(
+            Ok(b"ATGCA".to_vec()),
+            reader.fetch("fastq1", 60, 65),
+            "Should fetch bases from a fastq file",
+        );
+
+        let mut reader = example();
+        assert!(
+            reader.fetch_quality("one", 0, 1).is_err(),
+            "Should return an error for fasta records",
+        );
+    }
+
+    #[test]
+    fn test_indexed_reader_fetch_bgzf() {
+        let plain = example();
+        let input = plain.reader.into_inner();
+        let records = plain.records;
+        let compressed = crate::io::bgzf::compress(input, 16);
+        let mut index_reader = crate::io::bgzf::Reader::new(std::io::Cursor::new(&compressed));
+        let index = index_reader.index().unwrap().clone();
+        let reader = crate::io::bgzf::Reader::with_index(std::io::Cursor::new(&compressed), index);
+        let mut reader = IndexedReader::new(reader, records);
+        assert_eq!(
+            Ok(b"CATGCATG".to_vec()),
+            reader.fetch("one", 27, 35),
+            "Should fetch across BGZF blocks",
+        );
+        assert_eq!(
+            Ok(b"GCATGC".to_vec()),
+            reader.fetch("two", 22, 28),
+            "Should fetch from a later block",
+        );
+    }
+
+    #[test]
+    fn test_indexed_reader_record() {
+        let reader = example();
+        assert_eq!(
+            Some(28),
+            reader.record("two").map(|record| record.length),
+            "Should look up a record by name",
+        );
+        assert_eq!(
+            None,
+            reader.record("three"),
+            "Should return None if missing"
+        );
+    }
+
+    #[test]
+    fn test_indexed_reader_invalid_line_width() {
+        let mut records = example().records().to_vec();
+        records[1].line_width = 10;
+        let mut reader = IndexedReader::new(std::io::Cursor::new(b""), records.clone());
+        let error = reader.fetch("two", 0, 4).unwrap_err();
+        assert_eq!(
+            (ErrorKind::Input, Some("two".to_string())),
+            (error.kind, error.location.record),
+            "Should reject a record with lines shorter than their bases",
+        );
+
+        let path = std::env::temp_dir().join(format!(
+            "rust-samtools-{}-invalid-line-width.fai",
+            std::process::id()
+        ));
+        let mut writer = super::super::Writer::new(std::fs::File::create(&path).unwrap());
+        for record in records.iter() {
+            writer.write(record).unwrap();
+        }
+        writer.flush().unwrap();
+        drop(writer);
+        let error = IndexedReader::from_path("input.fa", &path)
+            .map(|_| ())
+            .unwrap_err();
+        assert_eq!(
+            (
+                ErrorKind::Input,
+                Some("two".to_string()),
+                Some(path.clone())
+            ),
+            (error.kind, error.location.record, error.location.path),
+            "Should reject the record when loading the index",
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_indexed_reader_validate() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a [u8],
+            expected: Option<&'a str>,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should accept an input matching its index",
+                input: b">one\nATGCATGCATGCATGCATGCATGCATGCAT\nGCATGCATGCATGCATGCATGCATGCATGC\nATGCAT\n>two another chromosome\nATGCATGCATGCAT\nGCATGCATGCATGC\n",
+                expected: None,
+            },
+            TestCase {
+                name: "Should reject an input whose header changed",
+                input: b">one\nATGCATGCATGCATGCATGCATGCATGCAT\nGCATGCATGCATGCATGCATGCATGCATGC\nATGCAT\n>tw0 another chromosome\nATGCATGCATGCAT\nGCATGCATGCATGC\n",
+                expected: Some("two"),
+            },
+            TestCase {
+                name: "Should reject an input whose last line grew",
+                input: b">one\nATGCATGCATGCATGCATGCATGCATGCAT\nGCATGCATGCATGCATGCATGCATGCATGC\nATGCATG\n>two another chromosome\nATGCATGCATGCAT\nGCATGCATGCATGC\n",
+                expected: Some("one"),
+            },
+            TestCase {
+                name: "Should reject a truncated input",
+                input: b">one\nATGCATGCATGCATGCATGCATGCATGCAT\nGCATGCATGCATGCATGCATGCATGCATGC\nATGCAT\n>two another chromosome\nATGCATGCATGCAT\nGCATGC",
+                expected: Some("two"),
+            },
+        ];
+        for test_case in test_cases {
+            let mut reader = IndexedReader::new(
+                std::io::Cursor::new(test_case.input),
+                example().records().to_vec(),
+            );
+            let actual = reader.validate(2).map_err(|e| (e.kind, e.location.record));
+            let expected = match test_case.expected {
+                Some(name) => Err((ErrorKind::Input, Some(name.to_string()))),
+                None => Ok(()),
+            };
+            assert_eq!(expected, actual, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_indexed_reader_validate_renamed() {
+        let input: &[u8] = b">chr1\nACGT\n>chr2\nGGCCTTAA\n>chr1 copy\nTTGCA\n";
+        let (records, _) = super::super::Indexer::new(input, super::super::IndexerFormat::FASTA)
+            .index(super::super::Duplicates::Rename)
+            .unwrap();
+        let mut reader = IndexedReader::new(std::io::Cursor::new(input), records);
+        assert_eq!(
+            Ok(()),
+            reader.validate(3),
+            "Should match a renamed record with the header it was renamed from",
+        );
+        assert_eq!(
+            Ok(b"TGC".to_vec()),
+            reader.fetch("chr1_2", 1, 4),
+            "Should fetch a renamed sequence",
+        );
+        assert_eq!(
+            Ok(b"CCTT".to_vec()),
+            reader.fetch("chr2", 2, 6),
+            "Should fetch a sequence that was not renamed",
+        );
+        let mut reader = IndexedReader::new(
+            std::io::Cursor::new(&b">chr1\nACGT\n>chr3\nTTGCA\n"[..]),
+            reader.records().to_vec(),
+        );
+        assert_eq!(
+            Err((ErrorKind::Input, Some("chr2".to_string()))),
+            reader.validate(3).map_err(|e| (e.kind, e.location.record)),
+            "Should still reject a changed input",
+        );
+    }
+
+    #[test]
+    fn test_is_header() {
+        struct TestCase<'a> {
+            name: &'a str,
+            line: &'a [u8],
+            name_split: NameSplit,
+            expected: bool,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should match a header with only the name",
+                line: b">chr1",
+                name_split: NameSplit::Whitespace,
+                expected: true,
+            },
+            TestCase {
+                name: "Should match a header with a description",
+                line: b">chr1 description",
+                name_split: NameSplit::Whitespace,
+                expected: true,
+            },
+            TestCase {
+                name: "Should match a header with a tab",
+                line: b">chr1\tdescription",
+                name_split: NameSplit::Whitespace,
+                expected: true,
+            },
+            TestCase {
+                name: "Should match a header with leading whitespace",
+                line: b">  chr1",
+                name_split: NameSplit::Whitespace,
+                expected: true,
+            },
+            TestCase {
+                name: "Should not match a name with a suffix",
+                line: b">chr1_alt",
+                name_split: NameSplit::Whitespace,
+                expected: false,
+            },
+            TestCase {
+                name: "Should not match a name with a version",
+                line: b">chr1.1",
+                name_split: NameSplit::Whitespace,
+                expected: false,
+            },
+            TestCase {
+                name: "Should not match a name with a dash",
+                line: b">chr1-x",
+                name_split: NameSplit::Whitespace,
+                expected: false,
+            },
+            TestCase {
+                name: "Should match a name split with a delimiter",
+                line: b">chr1|description",
+                name_split: NameSplit::Delimiter('|'),
+                expected: true,
+            },
+            TestCase {
+                name: "Should not match a whole line name with a description",
+                line: b">chr1 description",
+                name_split: NameSplit::Line,
+                expected: false,
+            },
+            TestCase {
+                name: "Should not match a line with another prefix",
+                line: b"@chr1",
+                name_split: NameSplit::Whitespace,
+                expected: false,
+            },
+        ];
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                is_header(test_case.line, FASTA_PREFIX, "chr1", test_case.name_split),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_sample() {
+        assert_eq!(vec![0, 1, 2], sample(3, 5), "Should sample every record");
+        assert_eq!(vec![0, 4, 9], sample(10, 3), "Should include the ends");
+        assert_eq!(vec![0], sample(10, 1), "Should sample the first record");
+    }
+}
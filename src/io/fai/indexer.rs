@@ -1,9 +1,12 @@
 use super::super::common;
-use super::{ReadToFai, Record, Records};
+use super::super::fastq::Strictness;
+use super::super::sequence::{Md5, NameSplit};
+use super::super::source::{Access, Mapped, Source};
+use super::{deduplicate, parallel, DuplicateWarning, Duplicates, ReadToFai, Record, Records};
 use crate::errors::{Error, ErrorKind, Result};
-use std::io::{BufRead, Seek};
 
 /// Format represents the input format to be indexed
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Format {
     FASTA,
@@ -29,53 +32,317 @@ impl Format {
 }
 
 /// Indexer indexes input into Fai records
+///
+/// Errors are located at the line, offset and record where they occurred, and at the input path
+/// if the indexer was constructed with [`Indexer::from_path`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::fai;
+///
+/// let fasta: &[u8] = b">chr1 description\nACGTACGT\nACGT\n";
+/// let indexer = fai::Indexer::new(std::io::Cursor::new(fasta), fai::IndexerFormat::FASTA);
+/// let records = indexer
+///     .iter()
+///     .collect::<rust_samtools::errors::Result<Vec<_>>>()?;
+/// assert_eq!("chr1", records[0].name);
+/// assert_eq!(12, records[0].length);
+/// assert_eq!(18, records[0].offset);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
 pub struct Indexer<R>
 where
-    R: std::io::Read + std::io::Seek,
+    R: std::io::Read,
 {
-    reader: std::io::BufReader<R>,
+    input: Input<R>,
     format: Format,
-    buffer: Vec<u8>,
-    sequence_num_bytes: usize,
     eof: bool,
+    md5: Option<Md5>,
+    strictness: Strictness,
+    name_split: NameSplit,
+    threads: usize,
+    path: Option<std::path::PathBuf>,
+    position: common::Position,
+    blank_line: Option<Line>,
+    short_line: Option<Line>,
+}
+
+/// Input holds the last line read by an indexer
+enum Input<R>
+where
+    R: std::io::Read,
+{
+    /// Lines are copied from a buffered reader
+    Read {
+        reader: std::io::BufReader<R>,
+        line: Vec<u8>,
+    },
+    /// Lines are borrowed from memory mapped data without copying
+    Mapped {
+        reader: R,
+        data: Mapped,
+        line: std::ops::Range<usize>,
+    },
+}
+
+impl<R> Input<R>
+where
+    R: std::io::Read,
+{
+    /// Last line read, which is empty if it was cleared
+    fn line(&self) -> &[u8] {
+        match self {
+            Self::Read { line, .. } => line,
+            Self::Mapped { data, line, .. } => &data.as_ref()[line.clone()],
+        }
+    }
+
+    /// Clear the last line read
+    fn clear(&mut self) {
+        match self {
+            Self::Read { line, .. } => line.clear(),
+            Self::Mapped { line, .. } => *line = 0..0,
+        }
+    }
+
+    /// Read the next line in place of the last one
+    fn read_line(&mut self, position: &mut common::Position) -> Result<usize> {
+        match self {
+            Self::Read { reader, line } => {
+                line.clear();
+                common::read_line(reader, line, position)
+            }
+            Self::Mapped { data, line, .. } => {
+                *line = 0..0;
+                *line = common::slice_line(data.as_ref(), position)?;
+                Ok(line.len())
+            }
+        }
+    }
+
+    /// Get the memory mapped data, if lines are borrowed from it
+    fn mapped(&self) -> Option<&Mapped> {
+        match self {
+            Self::Read { .. } => None,
+            Self::Mapped { data, .. } => Some(data),
+        }
+    }
+
+    /// Consume the input and return the underlying reader
+    fn into_inner(self) -> R {
+        match self {
+            Self::Read { reader, .. } => reader.into_inner(),
+            Self::Mapped { reader, .. } => reader,
+        }
+    }
+}
+
+/// Line records the position and length of a sequence line
+#[derive(Clone, Copy, Debug)]
+struct Line {
+    number: usize,
+    offset: u64,
+    bases: usize,
+    bytes: usize,
+}
+
+impl Line {
+    /// Add the location of the line to an error
+    fn locate(&self, error: Error) -> Error {
+        error.with_line(self.number).with_offset(self.offset)
+    }
 }
 
 impl<R> Indexer<R>
 where
-    R: std::io::Read + std::io::Seek,
+    R: std::io::Read,
 {
     /// Construct a new indexer
     pub fn new(reader: R, format: Format) -> Self {
+        Self::with_input(
+            Input::Read {
+                reader: std::io::BufReader::new(reader),
+                line: Vec::new(),
+            },
+            format,
+        )
+    }
+
+    /// Construct an indexer that reads the lines of `data` in place, without copying them
+    ///
+    /// `reader` is only returned by [`Indexer::into_inner`].
+    ///
+    fn with_mapped(reader: R, data: Mapped, format: Format) -> Self {
+        Self::with_input(
+            Input::Mapped {
+                reader,
+                data,
+                line: 0..0,
+            },
+            format,
+        )
+    }
+
+    /// Construct an indexer from its input
+    fn with_input(input: Input<R>, format: Format) -> Self {
         Self {
-            reader: std::io::BufReader::new(reader),
+            input,
             format,
-            buffer: Vec::new(),
-            sequence_num_bytes: 0,
             eof: false,
+            md5: None,
+            strictness: Strictness::default(),
+            name_split: NameSplit::default(),
+            threads: 1,
+            path: None,
+            position: common::Position::default(),
+            blank_line: None,
+            short_line: None,
         }
     }
 
+    /// Compute the MD5 of each sequence while indexing
+    ///
+    /// The checksum is stored in [`Record::md5`] and is computed over the upper-cased bases with
+    /// whitespace removed, as used by `@SQ M5` tags.
+    ///
+    pub fn with_md5(mut self) -> Self {
+        self.md5 = Some(Md5::new());
+        self
+    }
+
+    /// Set how strictly FASTQ entries are validated
+    pub fn with_strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    /// Set the rule for splitting sequence names from description lines
+    pub fn with_name_split(mut self, name_split: NameSplit) -> Self {
+        self.name_split = name_split;
+        self
+    }
+
+    /// Index with up to `threads` worker threads in [`Indexer::index`]
+    ///
+    /// Only uncompressed FASTA input constructed with [`Indexer::from_path`] or
+    /// [`Indexer::from_path_with`] is indexed in parallel, and memory mapped input is shared with
+    /// the workers.  Other input is indexed sequentially.
+    ///
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
     /// Consume a reader by iterating over it
     pub fn iter(self) -> Records<Indexer<R>> {
         Records::new(self)
     }
 
+    /// Index the whole input, applying a duplicate name policy
+    ///
+    /// The kept records are returned in input order with a warning for every record that was
+    /// dropped or renamed.  See [`deduplicate`].  The records, and the location of the first
+    /// error, are the same whether or not the input is indexed in parallel.
+    ///
+    pub fn index(
+        &mut self,
+        duplicates: Duplicates,
+    ) -> Result<(Vec<Record>, Vec<DuplicateWarning>)> {
+        let records = match self.index_parallel() {
+            Ok(Some(records)) => records,
+            Ok(None) => self.index_sequential()?,
+            Err(e) => return Err(self.locate_path(e)),
+        };
+        deduplicate(records, duplicates).map_err(|e| self.locate_path(e))
+    }
+
+    /// Add the input path to an error, if the indexer was constructed from a path
+    fn locate_path(&self, error: Error) -> Error {
+        match &self.path {
+            Some(path) => error.with_path(path),
+            None => error,
+        }
+    }
+
+    /// Index the input on worker threads, if it can be
+    fn index_parallel(&self) -> Result<Option<Vec<Record>>> {
+        let path = match &self.path {
+            Some(path) if self.threads > 1 && self.format == Format::FASTA => path,
+            _ => return Ok(None),
+        };
+        if self.position.end > 0 {
+            return Ok(None);
+        }
+        let md5 = self.md5.is_some();
+        let name_split = self.name_split;
+        parallel::index(path, self.threads, self.input.mapped(), |segment| {
+            let indexer = Indexer::from_segment(segment).with_name_split(name_split);
+            if md5 {
+                indexer.with_md5()
+            } else {
+                indexer
+            }
+        })
+    }
+
+    /// Index the remaining input on the current thread
+    fn index_sequential(&mut self) -> Result<Vec<Record>> {
+        let mut records = Vec::new();
+        loop {
+            let mut record = Record::new();
+            match self.read(&mut record) {
+                Ok(()) => records.push(record),
+                Err(err) if err.kind == ErrorKind::Eof => return Ok(records),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Consume the indexer and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.input.into_inner()
+    }
+
+    /// Read a Fai record without locating errors
+    fn read_record(&mut self, record: &mut Record) -> Result<()> {
+        self.read_description(record)?;
+        self.read_sequence(record)?;
+        self.read_plus(record)?;
+        self.read_quality(record)?;
+        Ok(())
+    }
+
+    /// Add the location of the last line read to an error
+    fn locate(&self, error: Error, record: &Record) -> Error {
+        self.locate_path(self.position.locate(error).with_record(&record.name))
+    }
+
     /// Read the first line of the input entry
     fn read_description(&mut self, record: &mut Record) -> Result<()> {
-        if self.buffer.is_empty() {
+        if self.input.line().is_empty() {
             self.read_line()?;
         }
-        record.name = get_name(&self.buffer, self.format)?;
-        record.offset = self.reader.stream_position()?;
-        self.buffer.clear();
+        record.name = get_name(self.input.line(), self.format, self.name_split)?;
+        record.offset = self.position.end;
+        self.input.clear();
         Ok(())
     }
 
     /// Read the entire sequence
+    ///
+    /// Blank lines are allowed at the end of the sequence, but not between sequence lines.  All
+    /// lines must have the same length except the last, which may be shorter.
+    ///
     fn read_sequence(&mut self, record: &mut Record) -> Result<()> {
-        self.sequence_num_bytes = 0;
+        self.blank_line = None;
+        self.short_line = None;
         loop {
-            if is_sequence_end(&self.buffer, self.format) || self.eof {
+            if is_sequence_end(self.input.line(), self.format) || self.eof {
+                if let Some(md5) = self.md5.as_mut() {
+                    record.md5 = Some(std::mem::take(md5).finalize());
+                }
                 return Ok(());
             }
             self.read_sequence_line(record)?;
@@ -84,25 +351,45 @@ where
 
     /// Read in a sequence line
     fn read_sequence_line(&mut self, record: &mut Record) -> Result<()> {
-        self.buffer.clear();
         let num_bytes = self.read_line()?;
-        if is_sequence_end(&self.buffer, self.format) {
+        if is_sequence_end(self.input.line(), self.format) || num_bytes == 0 {
+            return Ok(());
+        }
+        let line = Line {
+            number: self.position.line,
+            offset: self.position.offset,
+            bases: common::count_bases(self.input.line())?,
+            bytes: num_bytes,
+        };
+        if line.bases == 0 {
+            self.blank_line.get_or_insert(line);
             return Ok(());
         }
+        if let Some(blank_line) = self.blank_line {
+            return Err(
+                blank_line.locate(Error::new(ErrorKind::Input, "blank line inside sequence"))
+            );
+        }
+        check_line_length(record, line, self.short_line)?;
         if record.line_width == 0 {
-            record.line_width = num_bytes;
-            record.line_bases = common::count_bases(&self.buffer)?;
-        } else if record.line_width < num_bytes {
-            return Err(Error::new(ErrorKind::Input, "invalid record"));
+            record.line_width = line.bytes;
+            record.line_bases = line.bases;
+        } else if line.bases < record.line_bases || line.bytes < record.line_width {
+            self.short_line = Some(line);
+        }
+        record.length += line.bases;
+        if let Some(md5) = self.md5.as_mut() {
+            md5.update(self.input.line());
         }
-        self.sequence_num_bytes += num_bytes;
-        record.length += common::count_bases(&self.buffer)?;
         Ok(())
     }
 
-    /// Read in a line of data
+    /// Read in a line of data in place of the last line
+    ///
+    /// At the end of the input, the last line is cleared and 0 is returned once.
+    ///
     fn read_line(&mut self) -> Result<usize> {
-        match common::read_line(&mut self.reader, &mut self.buffer) {
+        match self.input.read_line(&mut self.position) {
             Err(e) if e.kind == ErrorKind::Eof => {
                 if self.eof {
                     Err(e)
@@ -116,60 +403,190 @@ where
     }
 
     /// Read in the plus line
+    ///
+    /// The name after the `+` is checked as described in [`Strictness::check_separator`].
+    ///
     fn read_plus(&mut self, record: &mut Record) -> Result<()> {
         if self.format == Format::FASTA {
             return Ok(());
         }
-        record.qual_offset = Some(self.reader.stream_position()?);
-        self.buffer.clear();
+        if !is_sequence_end(self.input.line(), self.format) {
+            return Err(self
+                .position
+                .locate_next(Error::new(ErrorKind::Input, "missing '+' line")));
+        }
+        self.strictness
+            .check_separator(self.input.line(), &record.name, self.name_split)?;
+        record.qual_offset = Some(self.position.end);
+        self.input.clear();
         Ok(())
     }
 
     /// Read the quality portion
-    fn read_quality(&mut self) -> Result<()> {
+    ///
+    /// Quality lines are read until they hold as many qualities as the sequence has bases, so
+    /// quality lines starting with `@` or `+` are not mistaken for entry boundaries.  Quality lines
+    /// must be wrapped like the sequence lines.  Blank lines after the qualities are skipped and
+    /// the next description line is left in the buffer.
+    ///
+    fn read_quality(&mut self, record: &Record) -> Result<()> {
         if self.format == Format::FASTA {
             return Ok(());
         }
-        self.reader.consume(self.sequence_num_bytes);
-        self.read_line()?;
-        Ok(())
+        let mut num_qualities = 0;
+        while num_qualities < record.length {
+            let num_bytes = self.read_line()?;
+            if num_bytes == 0 {
+                return Err(self.position.locate_next(quality_length_error(
+                    &format!("found {} qualities", num_qualities),
+                    record,
+                )));
+            }
+            let qualities = common::count_bases(self.input.line())?;
+            if qualities == 0 {
+                return Err(Error::new(ErrorKind::Input, "blank line inside quality"));
+            }
+            let expected = record.line_bases.min(record.length - num_qualities);
+            num_qualities += qualities;
+            if num_qualities > record.length {
+                return Err(quality_length_error(
+                    &format!("found at least {} qualities", num_qualities),
+                    record,
+                ));
+            }
+            if qualities != expected
+                || (num_qualities < record.length && num_bytes != record.line_width)
+            {
+                return Err(Error::new(
+                    ErrorKind::Input,
+                    &format!(
+                        "inconsistent quality line length: found {} qualities in {} bytes, \
+                         expected {} qualities in {} bytes; quality lines must be wrapped like \
+                         the sequence",
+                        qualities,
+                        num_bytes,
+                        expected,
+                        expected + record.line_width - record.line_bases,
+                    ),
+                ));
+            }
+        }
+        loop {
+            if self.read_line()? == 0 || common::count_bases(self.input.line())? > 0 {
+                return Ok(());
+            }
+        }
     }
 }
 
 impl<R> ReadToFai for Indexer<R>
 where
-    R: std::io::Read + std::io::Seek,
+    R: std::io::Read,
 {
     /// Read a Fai record
     fn read(&mut self, record: &mut Record) -> Result<()> {
-        self.read_description(record)?;
-        self.read_sequence(record)?;
-        self.read_plus(record)?;
-        self.read_quality()?;
-        Ok(())
+        record.clear();
+        self.read_record(record).map_err(|e| self.locate(e, record))
     }
 }
 
-impl Indexer<std::fs::File> {
+impl Indexer<Source> {
     /// Construct an indexer from path
+    ///
+    /// BGZF compressed input is decompressed and offsets refer to the uncompressed data.
+    ///
     pub fn from_path<P: AsRef<std::path::Path>>(path: P, format: Format) -> Result<Self> {
-        let file = std::fs::File::open(path)?;
-        Ok(Indexer::new(file, format))
+        Self::from_path_with(path, format, Access::default())
+    }
+
+    /// Construct an indexer from path, reading uncompressed input with `access`
+    ///
+    /// Lines of memory mapped input are indexed in place, without copying them.  Any `.gzi` index
+    /// of BGZF input is ignored, so the index of the source is rebuilt from the blocks read.
+    ///
+    pub fn from_path_with<P: AsRef<std::path::Path>>(
+        path: P,
+        format: Format,
+        access: Access,
+    ) -> Result<Self> {
+        let source = Source::from_path_unindexed(&path, access)?;
+        let mut indexer = match source.mapped().cloned() {
+            Some(data) => Indexer::with_mapped(source, data, format),
+            None => Indexer::new(source, format),
+        };
+        indexer.path = Some(path.as_ref().to_path_buf());
+        Ok(indexer)
+    }
+}
+
+impl Indexer<parallel::Segment> {
+    /// Construct a FASTA indexer for a segment of the input
+    pub(super) fn from_segment(segment: parallel::Segment) -> Self {
+        match segment.mapped().cloned() {
+            Some(data) => Indexer::with_mapped(segment, data, Format::FASTA),
+            None => Indexer::new(segment, Format::FASTA),
+        }
+    }
+
+    /// Index the whole segment, returning its records and the number of lines read
+    ///
+    /// Errors are located relative to the start of the segment.
+    ///
+    pub(super) fn index_segment(&mut self) -> Result<(Vec<Record>, usize)> {
+        let records = self.index_sequential()?;
+        Ok((records, self.position.line))
     }
 }
 
+/// Error for qualities that don't match the length of the sequence
+fn quality_length_error(found: &str, record: &Record) -> Error {
+    Error::new(
+        ErrorKind::Input,
+        &format!(
+            "quality length does not match sequence length: {}, expected {}",
+            found, record.length
+        ),
+    )
+}
+
+/// Check a sequence line against the line length of the record
+///
+/// Every line must match the length of the first line, except the last line which may be
+/// shorter.  `short_line` is a previous line of the record that was shorter than the first.
+///
+fn check_line_length(record: &Record, line: Line, short_line: Option<Line>) -> Result<()> {
+    if record.line_width == 0 {
+        return Ok(());
+    }
+    let (line, reason) = match short_line {
+        Some(short_line) => (short_line, "only the last line may be shorter"),
+        None if line.bases > record.line_bases || line.bytes > record.line_width => {
+            (line, "lines may not be longer than the first line")
+        }
+        None => return Ok(()),
+    };
+    Err(line.locate(Error::new(
+        ErrorKind::Input,
+        &format!(
+            "inconsistent line length: found {} bases in {} bytes, expected {} bases in {} \
+             bytes; {}",
+            line.bases, line.bytes, record.line_bases, record.line_width, reason
+        ),
+    )))
+}
+
 /// Check to see if the line is a description line
 fn is_description(line: &[u8], format: Format) -> bool {
     line.starts_with(&[format.description_prefix()])
 }
 
 /// Retrieve name from the description line
-fn get_name(description: &[u8], format: Format) -> Result<String> {
+fn get_name(description: &[u8], format: Format, name_split: NameSplit) -> Result<String> {
     if !is_description(description, format) {
         return Err(Error::new(ErrorKind::Input, "invalid input format"));
     }
-    let description = String::from_utf8(description[1..].to_vec())?;
-    Ok(common::parse_sequence_name(&description))
+    let description = std::str::from_utf8(&description[1..])?;
+    Ok(name_split.split(description).0.into())
 }
 
 /// Check to see if the line has a sequence end marker
@@ -203,6 +620,7 @@ GCATGCATGCATGC
             line_bases: 30,
             line_width: 31,
             qual_offset: None,
+            md5: None,
         };
         assert!(
             indexer.read(&mut record).is_ok(),
@@ -218,6 +636,7 @@ GCATGCATGCATGC
             line_bases: 14,
             line_width: 15,
             qual_offset: None,
+            md5: None,
         };
         assert!(
             indexer.read(&mut record).is_ok(),
@@ -254,6 +673,7 @@ IIA94445EEII==
             line_bases: 30,
             line_width: 31,
             qual_offset: Some(79),
+            md5: None,
         };
         assert!(
             reader.read(&mut record).is_ok(),
@@ -269,6 +689,7 @@ IIA94445EEII==
             line_bases: 14,
             line_width: 15,
             qual_offset: Some(188),
+            md5: None,
         };
         assert!(
             reader.read(&mut record).is_ok(),
@@ -277,6 +698,426 @@ IIA94445EEII==
         assert_eq!(expected, record, "Should work for example in documentation",);
     }
 
+    #[test]
+    fn test_crlf() {
+        let input: &[u8] = b">one\r\nACGTA\r\nCGTAC\r\nGT\r\n>two\r\nAC\r\n";
+        let indexer = Indexer::new(std::io::Cursor::new(input), Format::FASTA);
+        let actual: Result<Vec<Record>> = indexer.iter().collect();
+        assert_eq!(
+            Ok(vec![
+                Record {
+                    name: "one".into(),
+                    length: 12,
+                    offset: 6,
+                    line_bases: 5,
+                    line_width: 7,
+                    qual_offset: None,
+                    md5: None,
+                },
+                Record {
+                    name: "two".into(),
+                    length: 2,
+                    offset: 30,
+                    line_bases: 2,
+                    line_width: 4,
+                    qual_offset: None,
+                    md5: None,
+                },
+            ]),
+            actual,
+            "Should count line terminators in the line width of CRLF files",
+        );
+    }
+
+    #[test]
+    fn test_blank_lines() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a [u8],
+            expected: Result<Vec<(&'a str, usize)>>,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should accept blank lines at the end of an entry",
+                input: b">one\nACGT\nAC\n\n\r\n>two\nACG\n",
+                expected: Ok(vec![("one", 6), ("two", 3)]),
+            },
+            TestCase {
+                name: "Should accept blank lines at the end of the file",
+                input: b">one\nACGT\nAC\n\n  \n",
+                expected: Ok(vec![("one", 6)]),
+            },
+            TestCase {
+                name: "Should accept an entry with only blank lines",
+                input: b">one\n\n>two\nACG\n",
+                expected: Ok(vec![("one", 0), ("two", 3)]),
+            },
+            TestCase {
+                name: "Should reject blank lines inside a sequence",
+                input: b">one\nACGT\n>two\nACGT\n\nAC\n",
+                expected: Err(Error::new(ErrorKind::Input, "blank line inside sequence")
+                    .with_line(5)
+                    .with_offset(20)
+                    .with_record("two")),
+            },
+            TestCase {
+                name: "Should reject blank lines before a sequence",
+                input: b">one\n\r\nACGT\n",
+                expected: Err(Error::new(ErrorKind::Input, "blank line inside sequence")
+                    .with_line(2)
+                    .with_offset(5)
+                    .with_record("one")),
+            },
+        ];
+        for test_case in test_cases {
+            let indexer = Indexer::new(std::io::Cursor::new(test_case.input), Format::FASTA);
+            let actual: Result<Vec<(String, usize)>> = indexer
+                .iter()
+                .map(|record| record.map(|record| (record.name, record.length)))
+                .collect();
+            let expected = test_case.expected.map(|records| {
+                records
+                    .into_iter()
+                    .map(|(name, length)| (name.to_string(), length))
+                    .collect()
+            });
+            assert_eq!(expected, actual, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_line_lengths() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a [u8],
+            expected: Result<usize>,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should accept a shorter last line",
+                input: b">one\nACGT\nACGT\nAC\n",
+                expected: Ok(10),
+            },
+            TestCase {
+                name: "Should accept a last line without a newline",
+                input: b">one\nACGT\nACGT",
+                expected: Ok(8),
+            },
+            TestCase {
+                name: "Should reject a short line that is not the last",
+                input: b">one\nACGT\nAC\nACGT\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "inconsistent line length: found 2 bases in 3 bytes, expected 4 bases in 5 \
+                     bytes; only the last line may be shorter",
+                )
+                .with_line(3)
+                .with_offset(10)
+                .with_record("one")),
+            },
+            TestCase {
+                name: "Should reject a line longer than the first",
+                input: b">one\nACGT\nACGTA\nAC\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "inconsistent line length: found 5 bases in 6 bytes, expected 4 bases in 5 \
+                     bytes; lines may not be longer than the first line",
+                )
+                .with_line(3)
+                .with_offset(10)
+                .with_record("one")),
+            },
+            TestCase {
+                name: "Should reject a line with a different line terminator",
+                input: b">one\nACGT\nACGT\r\nAC\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "inconsistent line length: found 4 bases in 6 bytes, expected 4 bases in 5 \
+                     bytes; lines may not be longer than the first line",
+                )
+                .with_line(3)
+                .with_offset(10)
+                .with_record("one")),
+            },
+        ];
+        for test_case in test_cases {
+            let mut indexer = Indexer::new(std::io::Cursor::new(test_case.input), Format::FASTA);
+            let mut record = Record::new();
+            let actual = indexer.read(&mut record).map(|_| record.length);
+            assert_eq!(test_case.expected, actual, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_multiline_fastq() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a [u8],
+            expected: Result<Vec<(&'a str, usize, Option<u64>)>>,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should read wrapped qualities starting with @ and +",
+                input: b"@r1\nACGT\nAC\n+\n@@+@\n+@\n@r2\nA\n+\n@\n",
+                expected: Ok(vec![("r1", 6, Some(14)), ("r2", 1, Some(30))]),
+            },
+            TestCase {
+                name: "Should reject truncated qualities",
+                input: b"@r1\nACGT\nAC\n+\nIIII\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "quality length does not match sequence length: found 4 qualities, expected 6",
+                )
+                .with_line(6)
+                .with_offset(19)
+                .with_record("r1")),
+            },
+            TestCase {
+                name: "Should reject too many qualities",
+                input: b"@r1\nACGT\nAC\n+\nIIII\nIII\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "quality length does not match sequence length: found at least 7 qualities, \
+                     expected 6",
+                )
+                .with_line(6)
+                .with_offset(19)
+                .with_record("r1")),
+            },
+            TestCase {
+                name: "Should reject qualities wrapped differently from the sequence",
+                input: b"@r1\nACGT\nAC\n+\nIII\nIII\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "inconsistent quality line length: found 3 qualities in 4 bytes, expected 4 \
+                     qualities in 5 bytes; quality lines must be wrapped like the sequence",
+                )
+                .with_line(5)
+                .with_offset(14)
+                .with_record("r1")),
+            },
+            TestCase {
+                name: "Should reject a blank line inside the qualities",
+                input: b"@r1\nACGT\nAC\n+\nIIII\n\nII\n",
+                expected: Err(Error::new(ErrorKind::Input, "blank line inside quality")
+                    .with_line(6)
+                    .with_offset(19)
+                    .with_record("r1")),
+            },
+            TestCase {
+                name: "Should accept a + line repeating the header",
+                input: b"@r1 desc\nAC\n+r1 desc\nII\n",
+                expected: Ok(vec![("r1", 2, Some(21))]),
+            },
+            TestCase {
+                name: "Should reject a + line with a different name",
+                input: b"@r1\nAC\n+r2\nII\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "'+' line name r2 does not match header name",
+                )
+                .with_line(3)
+                .with_offset(7)
+                .with_record("r1")),
+            },
+            TestCase {
+                name: "Should reject a missing + line",
+                input: b"@r1\nACGT\n",
+                expected: Err(Error::new(ErrorKind::Input, "missing '+' line")
+                    .with_line(3)
+                    .with_offset(9)
+                    .with_record("r1")),
+            },
+        ];
+        for test_case in test_cases {
+            let indexer = Indexer::new(std::io::Cursor::new(test_case.input), Format::FASTQ);
+            let actual = indexer
+                .iter()
+                .map(|record| record.map(|r| (r.name, r.length, r.qual_offset)))
+                .collect::<Result<Vec<_>>>();
+            let expected = test_case.expected.map(|records| {
+                records
+                    .into_iter()
+                    .map(|(name, length, qual_offset)| (name.to_string(), length, qual_offset))
+                    .collect::<Vec<_>>()
+            });
+            assert_eq!(expected, actual, "{}", test_case.name);
+        }
+
+        let input: &[u8] = b"@r1\nAC\n+r2\nII\n";
+        let indexer = Indexer::new(std::io::Cursor::new(input), Format::FASTQ)
+            .with_strictness(Strictness::Lenient);
+        assert!(
+            indexer.iter().all(|record| record.is_ok()),
+            "Should ignore the + line name when lenient",
+        );
+    }
+
+    #[test]
+    fn test_with_md5() {
+        let input: &[u8] = b">one\nacgt\r\nAC\r\n>two\n>three\nACGTAC\n";
+        let indexer = Indexer::new(std::io::Cursor::new(input), Format::FASTA).with_md5();
+        let actual: Vec<Option<String>> =
+            indexer.iter().map(|record| record.unwrap().md5).collect();
+        assert_eq!(
+            vec![
+                Some("1617b7d879d437fa4c87da5875264b14".to_string()),
+                Some("d41d8cd98f00b204e9800998ecf8427e".to_string()),
+                Some("1617b7d879d437fa4c87da5875264b14".to_string()),
+            ],
+            actual,
+            "Should compute normalized checksums for each sequence",
+        );
+
+        let indexer = Indexer::new(std::io::Cursor::new(input), Format::FASTA);
+        assert_eq!(
+            None,
+            indexer.iter().next().unwrap().unwrap().md5,
+            "Should not compute checksums by default",
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        let input: &[u8] = b">one\nACGT\n>two\nAC\n>one\nA\n";
+        let mut indexer = Indexer::new(std::io::Cursor::new(input), Format::FASTA);
+        let (records, warnings) = indexer.index(Duplicates::KeepFirst).unwrap();
+        assert_eq!(
+            vec!["one", "two"],
+            records
+                .iter()
+                .map(|record| record.name.as_str())
+                .collect::<Vec<&str>>(),
+            "Should keep the first record with a duplicated name",
+        );
+        assert_eq!(1, warnings.len(), "Should warn about the dropped record");
+
+        let mut indexer = Indexer::new(std::io::Cursor::new(input), Format::FASTA);
+        let error = indexer.index(Duplicates::Error).unwrap_err();
+        assert_eq!(
+            (ErrorKind::Input, Some(23), Some("one".to_string())),
+            (error.kind, error.location.offset, error.location.record),
+            "Should locate a duplicate name at its record",
+        );
+    }
+
+    #[test]
+    fn test_bgzf_input() {
+        let input: &[u8] = b">one\nATGCATGCAT\nGCAT\n>two desc\nAT\n";
+        let plain: Result<Vec<Record>> = Indexer::new(std::io::Cursor::new(input), Format::FASTA)
+            .iter()
+            .collect();
+        let compressed = crate::io::bgzf::compress(input, 7);
+        let reader = crate::io::bgzf::Reader::new(std::io::Cursor::new(compressed));
+        let bgzf: Result<Vec<Record>> = Indexer::new(reader, Format::FASTA).iter().collect();
+        assert!(plain.is_ok());
+        assert_eq!(
+            plain, bgzf,
+            "Should index BGZF input with uncompressed offsets",
+        );
+    }
+
+    #[test]
+    fn test_mapped_input() {
+        struct TestCase<'a> {
+            name: &'a str,
+            file: &'a str,
+            input: &'a [u8],
+            format: Format,
+            expect_err: bool,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should index mapped FASTA like read FASTA",
+                file: "fasta",
+                input: b">one\nACGT\nAC\n\n>two desc\r\nACG",
+                format: Format::FASTA,
+                expect_err: false,
+            },
+            TestCase {
+                name: "Should index mapped FASTQ like read FASTQ",
+                file: "fastq",
+                input: b"@one\nACGT\nAC\n+\n@@@@\n++\n\n@two\nACG\n+two\nIII",
+                format: Format::FASTQ,
+                expect_err: false,
+            },
+            TestCase {
+                name: "Should locate errors in mapped input like in read input",
+                file: "error",
+                input: b"@one\nACGT\n+\nIII\n",
+                format: Format::FASTQ,
+                expect_err: true,
+            },
+        ];
+        for test_case in test_cases {
+            let path = std::env::temp_dir().join(format!(
+                "rust-samtools-{}-indexer-{}",
+                std::process::id(),
+                test_case.file
+            ));
+            std::fs::write(&path, test_case.input).unwrap();
+            let index = |access| {
+                Indexer::from_path_with(&path, test_case.format, access)?
+                    .iter()
+                    .collect::<Result<Vec<_>>>()
+            };
+            let expected = index(Access::Read);
+            assert_eq!(
+                test_case.expect_err,
+                expected.is_err(),
+                "{}",
+                test_case.name
+            );
+            assert_eq!(expected, index(Access::Mmap), "{}", test_case.name);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_stale_gzi() {
+        let input: &[u8] = b">one\nACGTACGT\nACGT\n>two\nACG\n";
+        let path = std::env::temp_dir().join(format!(
+            "rust-samtools-{}-indexer-stale.fa.gz",
+            std::process::id()
+        ));
+        let gzi_path = crate::io::source::gzi_path(&path);
+        let compressed = crate::io::bgzf::compress(input, 5);
+        std::fs::write(&path, &compressed).unwrap();
+        let mut expected = Vec::new();
+        let mut reader = crate::io::bgzf::Reader::new(std::io::Cursor::new(compressed));
+        std::io::copy(&mut reader, &mut std::io::sink()).unwrap();
+        reader.index().unwrap().write(&mut expected).unwrap();
+        // Index of the same input compressed with different blocks
+        let mut stale =
+            crate::io::bgzf::Reader::new(std::io::Cursor::new(crate::io::bgzf::compress(input, 7)));
+        std::io::copy(&mut stale, &mut std::io::sink()).unwrap();
+        stale
+            .index()
+            .unwrap()
+            .write(std::fs::File::create(&gzi_path).unwrap())
+            .unwrap();
+
+        let mut indexer = Indexer::from_path(&path, Format::FASTA).unwrap();
+        assert!(
+            indexer.index(Duplicates::default()).is_ok(),
+            "Should index the input without using the stale index",
+        );
+        let mut actual = Vec::new();
+        let mut source = indexer.into_inner();
+        source
+            .gzi_index()
+            .unwrap()
+            .unwrap()
+            .write(&mut actual)
+            .unwrap();
+        assert_eq!(
+            expected, actual,
+            "Should rebuild the index from the blocks read"
+        );
+        std::fs::remove_file(path).unwrap();
+        std::fs::remove_file(gzi_path).unwrap();
+    }
+
     #[test]
     fn test_is_description() {
         struct TestCase<'a> {
@@ -352,6 +1193,13 @@ IIA94445EEII==
                 expect_error: false,
                 expected: "rita".to_string(),
             },
+            TestCase {
+                name: "Should end the name at a tab",
+                description: b">chr1\tAC:CM000663.2",
+                format: Format::FASTA,
+                expect_error: false,
+                expected: "chr1".to_string(),
+            },
             TestCase {
                 name: "Should skip spaces after the prefix before name",
                 description: b"@     rita woof",
@@ -361,7 +1209,11 @@ IIA94445EEII==
             },
         ];
         for test_case in test_cases {
-            let actual = get_name(test_case.description, test_case.format);
+            let actual = get_name(
+                test_case.description,
+                test_case.format,
+                NameSplit::default(),
+            );
             if test_case.expect_error {
                 assert!(actual.is_err(), "{}", test_case.name);
             } else {
@@ -1,10 +1,15 @@
+mod duplicates;
+mod indexed_reader;
 mod indexer;
+mod parallel;
 mod reader;
 mod writer;
 
 use crate::errors::{Error, ErrorKind, Result};
 use serde::{Deserialize, Serialize};
 
+pub use duplicates::{deduplicate, DuplicateWarning, Duplicates};
+pub use indexed_reader::{Fetch, IndexedReader};
 pub use indexer::{Format as IndexerFormat, Indexer};
 pub use reader::Reader;
 pub use writer::Writer;
@@ -22,7 +27,7 @@ pub trait ReadToFai {
 ///
 /// [`documentation`]: https://www.htslib.org/doc/faidx.html
 ///
-#[derive(Debug, Deserialize, PartialEq, Serialize, Default)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Default)]
 pub struct Record {
     /// Name of this reference sequence
     pub name: String,
@@ -37,6 +42,10 @@ pub struct Record {
     /// Offset of sequence's first quality within the FASTQ file.  This is always `None` for
     /// FASTA fies.
     pub qual_offset: Option<u64>,
+    /// MD5 of the normalized sequence, if computed while indexing.  This is not part of the Fai
+    /// format.
+    #[serde(skip)]
+    pub md5: Option<String>,
 }
 
 impl Record {
@@ -45,6 +54,26 @@ impl Record {
         Self::default()
     }
 
+    /// Construct the Fai record of a sequence written with `line_width` bases per line
+    ///
+    /// Lines are assumed to end with a single newline, and a `line_width` of 0 means the sequence
+    /// is on a single line.  The line lengths match what [`Indexer`] finds for the same layout.
+    ///
+    pub fn wrapped(name: &str, length: usize, offset: u64, line_width: usize) -> Self {
+        let line_bases = match line_width {
+            0 => length,
+            line_width => length.min(line_width),
+        };
+        Self {
+            name: name.into(),
+            length,
+            offset,
+            line_bases,
+            line_width: if line_bases == 0 { 0 } else { line_bases + 1 },
+            ..Self::default()
+        }
+    }
+
     /// Convert a Fai record to a csv string record
     pub fn to_string_record(&self) -> csv::StringRecord {
         let mut record = vec![
@@ -60,6 +89,35 @@ impl Record {
         csv::StringRecord::from(record)
     }
 
+    /// Offset in the FASTA/FASTQ file of the base at the 0-based `position`
+    ///
+    /// `position` may be equal to the length of the sequence, in which case the offset just past
+    /// the last base is returned.
+    ///
+    pub fn base_offset(&self, position: usize) -> u64 {
+        self.offset + self.relative_offset(position)
+    }
+
+    /// Offset in the FASTQ file of the quality at the 0-based `position`
+    ///
+    /// Qualities are laid out with the same line lengths as bases.  `None` is returned for FASTA
+    /// records.
+    ///
+    pub fn quality_offset(&self, position: usize) -> Option<u64> {
+        self.qual_offset
+            .map(|qual_offset| qual_offset + self.relative_offset(position))
+    }
+
+    /// Offset of the 0-based `position` relative to the first base or quality
+    fn relative_offset(&self, position: usize) -> u64 {
+        if self.line_bases == 0 {
+            return 0;
+        }
+        let lines = position / self.line_bases;
+        let bases = position % self.line_bases;
+        (lines * self.line_width + bases) as u64
+    }
+
     /// Clear the Fai record
     pub fn clear(&mut self) {
         self.name.clear();
@@ -68,6 +126,7 @@ impl Record {
         self.line_bases = 0;
         self.line_width = 0;
         self.qual_offset = None;
+        self.md5 = None;
     }
 }
 
@@ -107,6 +166,11 @@ where
     pub fn new(reader: F) -> Self {
         Self { reader }
     }
+
+    /// Consume the iterator and return the reader
+    pub fn into_inner(self) -> F {
+        self.reader
+    }
 }
 
 impl<F> Iterator for Records<F>
@@ -150,6 +214,7 @@ mod tests {
                     line_bases: 10,
                     line_width: 4,
                     qual_offset: Some(3),
+                    md5: None,
                 },
             },
             TestCase {
@@ -163,6 +228,7 @@ mod tests {
                     line_bases: 10,
                     line_width: 4,
                     qual_offset: None,
+                    md5: None,
                 },
             },
             TestCase {
@@ -197,6 +263,83 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_fai_base_offset() {
+        let record = Record {
+            name: "one".into(),
+            length: 66,
+            offset: 5,
+            line_bases: 30,
+            line_width: 31,
+            qual_offset: None,
+            md5: None,
+        };
+        struct TestCase<'a> {
+            name: &'a str,
+            position: usize,
+            expected: u64,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should return the offset of the first base",
+                position: 0,
+                expected: 5,
+            },
+            TestCase {
+                name: "Should return the offset of the last base of a line",
+                position: 29,
+                expected: 34,
+            },
+            TestCase {
+                name: "Should skip the newline at the end of a line",
+                position: 30,
+                expected: 36,
+            },
+            TestCase {
+                name: "Should return the offset just past the last base",
+                position: 66,
+                expected: 73,
+            },
+        ];
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                record.base_offset(test_case.position),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_fai_quality_offset() {
+        let mut record = Record {
+            name: "fastq1".into(),
+            length: 66,
+            offset: 8,
+            line_bases: 30,
+            line_width: 31,
+            qual_offset: Some(79),
+            md5: None,
+        };
+        assert_eq!(
+            Some(79),
+            record.quality_offset(0),
+            "Should return the offset of the first quality",
+        );
+        assert_eq!(
+            Some(111),
+            record.quality_offset(31),
+            "Should skip the newline at the end of a line",
+        );
+        record.qual_offset = None;
+        assert_eq!(
+            None,
+            record.quality_offset(0),
+            "Should return None for fasta records",
+        );
+    }
+
     #[test]
     fn test_fai_to_string_record() {
         struct TestCase<'a> {
@@ -214,6 +357,7 @@ mod tests {
                     line_bases: 3,
                     line_width: 4,
                     qual_offset: None,
+                    md5: None,
                 },
                 expected: csv::StringRecord::from(vec!["name", "1", "2", "3", "4"]),
             },
@@ -226,6 +370,7 @@ mod tests {
                     line_bases: 3,
                     line_width: 4,
                     qual_offset: Some(5),
+                    md5: None,
                 },
                 expected: csv::StringRecord::from(vec!["name", "1", "2", "3", "4", "5"]),
             },
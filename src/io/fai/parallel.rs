@@ -0,0 +1,376 @@
+use super::indexer::Indexer;
+use super::Record;
+use crate::errors::{Error, Result};
+use crate::io::compression::{Compression, SNIFF_LEN};
+use crate::io::source::Mapped;
+use std::fs::File;
+use std::io::{BufRead, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const SCAN_LEN: usize = 1 << 16;
+const ENTRY_START: &[u8] = b"\n>";
+
+/// Segment of the input handed to each worker
+pub enum Segment {
+    /// Bytes of a file read with system calls
+    File(std::io::Take<File>),
+    /// Bytes of a memory mapped file
+    Mapped(std::io::Cursor<Mapped>),
+}
+
+impl Segment {
+    /// Get the memory mapped bytes of the segment, if it is mapped
+    pub fn mapped(&self) -> Option<&Mapped> {
+        match self {
+            Self::File(_) => None,
+            Self::Mapped(reader) => Some(reader.get_ref()),
+        }
+    }
+}
+
+impl Read for Segment {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::File(reader) => reader.read(buf),
+            Self::Mapped(reader) => reader.read(buf),
+        }
+    }
+}
+
+/// Index an uncompressed FASTA file on up to `threads` worker threads
+///
+/// The file is split into segments at lines starting with `>`, which are found by scanning from
+/// evenly spaced offsets concurrently.  Each segment is indexed by an indexer built with
+/// `indexer`, and the records are merged in file order with their offsets shifted to the start
+/// of their segment.  If `mapped` holds the memory mapped file, the workers share it instead of
+/// reading the file.  `None` is returned for compressed input, which is left to the sequential
+/// indexer.  The error of the first segment that fails is returned, located in the file with
+/// the lines and bytes of the segments before it.
+///
+pub fn index<F>(
+    path: &Path,
+    threads: usize,
+    mapped: Option<&Mapped>,
+    indexer: F,
+) -> Result<Option<Vec<Record>>>
+where
+    F: Fn(Segment) -> Indexer<Segment> + Sync,
+{
+    let len = match mapped {
+        Some(mapped) => mapped.len() as u64,
+        None => {
+            let mut file = File::open(path)?;
+            let mut header = Vec::new();
+            (&mut file)
+                .take(SNIFF_LEN as u64)
+                .read_to_end(&mut header)?;
+            if Compression::detect(&header) != Compression::None {
+                return Ok(None);
+            }
+            file.metadata()?.len()
+        }
+    };
+    let boundaries = std::thread::scope(|scope| {
+        let handles = (1..threads)
+            .map(|i| {
+                let from = len * i as u64 / threads as u64;
+                scope.spawn(move || match mapped {
+                    Some(mapped) => Ok(find_mapped_entry_start(mapped.as_ref(), from)),
+                    None => find_entry_start(path, from),
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(join).collect::<Result<Vec<_>>>()
+    })?;
+    let mut starts = vec![0];
+    starts.extend(boundaries.into_iter().flatten());
+    starts.dedup();
+    starts.push(len);
+    let indexer = &indexer;
+    let segments = std::thread::scope(|scope| {
+        let handles = starts
+            .windows(2)
+            .map(|segment| {
+                let (start, end) = (segment[0], segment[1]);
+                scope.spawn(move || {
+                    index_segment(open_segment(path, mapped, start, end)?, start, indexer)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles.into_iter().map(join).collect::<Vec<_>>()
+    });
+    let mut records = Vec::new();
+    let mut lines = 0;
+    for (segment, start) in segments.into_iter().zip(starts) {
+        let (segment_records, segment_lines) =
+            segment.map_err(|e| locate_segment_error(e, lines, start))?;
+        records.extend(segment_records);
+        lines += segment_lines;
+    }
+    Ok(Some(records))
+}
+
+/// Open the bytes `[start, end)` of a file, sharing its memory map if there is one
+fn open_segment(path: &Path, mapped: Option<&Mapped>, start: u64, end: u64) -> Result<Segment> {
+    if let Some(mapped) = mapped {
+        let data = mapped.slice(start as usize, end as usize);
+        return Ok(Segment::Mapped(std::io::Cursor::new(data)));
+    }
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::Start(start))?;
+    Ok(Segment::File(file.take(end - start)))
+}
+
+/// Index a segment starting at `start`, with offsets relative to the start of the file
+///
+/// The number of lines in the segment is returned along with its records.  Errors are located
+/// relative to the start of the segment.
+///
+fn index_segment<F>(segment: Segment, start: u64, indexer: &F) -> Result<(Vec<Record>, usize)>
+where
+    F: Fn(Segment) -> Indexer<Segment>,
+{
+    let (records, lines) = indexer(segment).index_segment()?;
+    let records = records
+        .into_iter()
+        .map(|record| Record {
+            offset: record.offset + start,
+            qual_offset: record.qual_offset.map(|offset| offset + start),
+            ..record
+        })
+        .collect();
+    Ok((records, lines))
+}
+
+/// Locate an error of a segment in the file, given the lines and bytes before the segment
+fn locate_segment_error(mut error: Error, lines: usize, start: u64) -> Error {
+    error.location.line = error.location.line.map(|line| line + lines);
+    error.location.offset = error.location.offset.map(|offset| offset + start);
+    error
+}
+
+/// Find the offset of the first line starting with `>` at or after `from`
+fn find_entry_start(path: &Path, from: u64) -> Result<Option<u64>> {
+    let mut file = File::open(path)?;
+    let mut position = from.saturating_sub(1);
+    file.seek(SeekFrom::Start(position))?;
+    let mut reader = std::io::BufReader::with_capacity(SCAN_LEN, file);
+    let mut last = None;
+    loop {
+        let buffer = reader.fill_buf()?;
+        if buffer.is_empty() {
+            return Ok(None);
+        }
+        if last == Some(ENTRY_START[0]) && buffer[0] == ENTRY_START[1] {
+            return Ok(Some(position));
+        }
+        if let Some(i) = buffer.windows(2).position(|bytes| bytes == ENTRY_START) {
+            return Ok(Some(position + i as u64 + 1));
+        }
+        last = buffer.last().copied();
+        position += buffer.len() as u64;
+        let num_bytes = buffer.len();
+        reader.consume(num_bytes);
+    }
+}
+
+/// Find the offset of the first line starting with `>` at or after `from` in memory
+fn find_mapped_entry_start(data: &[u8], from: u64) -> Option<u64> {
+    let position = from.saturating_sub(1);
+    data[position as usize..]
+        .windows(2)
+        .position(|bytes| bytes == ENTRY_START)
+        .map(|i| position + i as u64 + 1)
+}
+
+/// Wait for a worker thread, resuming its panic if it panicked
+fn join<T>(handle: std::thread::ScopedJoinHandle<'_, T>) -> T {
+    handle
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Duplicates, IndexerFormat, Writer};
+    use super::*;
+    use crate::errors::ErrorKind;
+    use crate::io::source::{Access, Source};
+
+    /// Write `data` to a temporary file unique to the test
+    fn temp_file(name: &str, data: &[u8]) -> std::path::PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("rust-samtools-{}-{}.fa", std::process::id(), name));
+        std::fs::write(&path, data).unwrap();
+        path
+    }
+
+    /// Generate FASTA entries with varied line widths, terminators and blank lines
+    fn fasta() -> Vec<u8> {
+        let mut data = Vec::new();
+        for i in 0..200 {
+            let terminator: &[u8] = if i % 7 == 0 { b"\r\n" } else { b"\n" };
+            data.extend_from_slice(format!(">seq{} description {}", i, i).as_bytes());
+            data.extend_from_slice(terminator);
+            let line_width = 10 + i % 50;
+            let length = (i * 37) % 500;
+            let bases = b"ACGTNacgtn".iter().cycle().take(length).copied();
+            for (j, base) in bases.enumerate() {
+                data.push(base);
+                if (j + 1) % line_width == 0 || j + 1 == length {
+                    data.extend_from_slice(terminator);
+                }
+            }
+            if i % 11 == 0 {
+                data.extend_from_slice(terminator);
+            }
+        }
+        data
+    }
+
+    /// Index a file and write its Fai records
+    fn index_file(path: &Path, threads: usize, access: Access) -> Result<Vec<u8>> {
+        let mut indexer = Indexer::from_path_with(path, IndexerFormat::FASTA, access)?
+            .with_md5()
+            .with_threads(threads);
+        let (records, _) = indexer.index(Duplicates::Error)?;
+        let mut output = Vec::new();
+        let mut writer = Writer::new(&mut output);
+        for record in records.iter() {
+            writer.write(record)?;
+        }
+        writer.flush()?;
+        drop(writer);
+        Ok(output)
+    }
+
+    #[test]
+    fn test_index() {
+        let path = temp_file("index", &fasta());
+        let expected = index_file(&path, 1, Access::Read);
+        assert!(expected.is_ok(), "Should index sequentially");
+        for access in [Access::Read, Access::Mmap] {
+            for threads in [1, 2, 3, 8, 64] {
+                assert_eq!(
+                    expected,
+                    index_file(&path, threads, access),
+                    "Should write the same index with {} threads and {:?} access",
+                    threads,
+                    access
+                );
+            }
+        }
+        let sequential = Indexer::from_path(&path, IndexerFormat::FASTA)
+            .unwrap()
+            .iter()
+            .collect::<Result<Vec<_>>>();
+        let parallel = index(&path, 8, None, Indexer::from_segment);
+        assert_eq!(
+            sequential.clone().map(Some),
+            parallel,
+            "Should index the segments without falling back",
+        );
+        let source = Source::from_path_with(&path, Access::Mmap).unwrap();
+        let parallel = index(&path, 8, source.mapped(), Indexer::from_segment);
+        assert_eq!(
+            sequential.map(Some),
+            parallel,
+            "Should index the mapped segments without falling back",
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_index_error() {
+        struct TestCase<'a> {
+            name: &'a str,
+            file: &'a str,
+            entry: &'a [u8],
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should locate a short line",
+                file: "index-error-short-line",
+                entry: b">bad\nACGT\nAC\nACGT\n",
+            },
+            TestCase {
+                name: "Should locate invalid UTF-8",
+                file: "index-error-utf8",
+                entry: b">bad\xff\nACGT\n",
+            },
+        ];
+        for test_case in test_cases {
+            let mut data = fasta();
+            data.extend_from_slice(test_case.entry);
+            data.extend_from_slice(&fasta());
+            let path = temp_file(test_case.file, &data);
+            let expected = index_file(&path, 1, Access::Read);
+            assert!(
+                expected.is_err(),
+                "{}: should fail sequentially",
+                test_case.name
+            );
+            for access in [Access::Read, Access::Mmap] {
+                assert_eq!(
+                    expected,
+                    index_file(&path, 4, access),
+                    "{}: should return the same error in parallel with {:?} access",
+                    test_case.name,
+                    access
+                );
+            }
+            let sequential = Indexer::from_path(&path, IndexerFormat::FASTA)
+                .unwrap()
+                .iter()
+                .collect::<Result<Vec<_>>>()
+                .map(Some)
+                .map_err(|e| (e.kind, e.location.line, e.location.offset));
+            let parallel = index(&path, 4, None, Indexer::from_segment)
+                .map_err(|e| (e.kind, e.location.line, e.location.offset));
+            assert_eq!(
+                sequential, parallel,
+                "{}: should locate the error without indexing sequentially",
+                test_case.name
+            );
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_index_io_error() {
+        let path = temp_file("index-io-error", &fasta());
+        let mut indexer = Indexer::from_path(&path, IndexerFormat::FASTA)
+            .unwrap()
+            .with_threads(4);
+        std::fs::remove_file(&path).unwrap();
+        let error = indexer.index(Duplicates::Error).unwrap_err();
+        assert_eq!(
+            (ErrorKind::IO, Some(path)),
+            (error.kind, error.location.path),
+            "Should return I/O errors of the workers instead of indexing sequentially",
+        );
+    }
+
+    #[test]
+    fn test_find_entry_start() {
+        let path = temp_file("find-entry-start", b">a\nAC\n>b\nAC\n");
+        let actual = [0, 1, 6, 7, 8]
+            .map(|from| find_entry_start(&path, from))
+            .to_vec();
+        assert_eq!(
+            vec![Ok(Some(6)), Ok(Some(6)), Ok(Some(6)), Ok(None), Ok(None)],
+            actual,
+            "Should find the next line starting with >",
+        );
+        let data = std::fs::read(&path).unwrap();
+        let actual = [0, 1, 6, 7, 8]
+            .map(|from| find_mapped_entry_start(&data, from))
+            .to_vec();
+        assert_eq!(
+            vec![Some(6), Some(6), Some(6), None, None],
+            actual,
+            "Should find the next line starting with > in memory",
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -2,9 +2,28 @@ use super::{ReadToFai, Record, Records, DELIMITER};
 use crate::errors::{Error, ErrorKind, Result};
 
 /// Reader is a reader for fai files
+///
+/// Errors are located at the line and offset of the offending record, and at the path of the
+/// file if the reader was constructed with [`Reader::from_path`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::fai;
+///
+/// let index: &[u8] = b"chr1\t12\t6\t8\t9\n";
+/// let records = fai::Reader::new(index)
+///     .iter()
+///     .collect::<rust_samtools::errors::Result<Vec<_>>>()?;
+/// assert_eq!("chr1", records[0].name);
+/// assert_eq!(12, records[0].length);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
 pub struct Reader<R: std::io::Read> {
     reader: csv::Reader<R>,
     string_record: csv::StringRecord,
+    path: Option<std::path::PathBuf>,
 }
 
 impl<R> Reader<R>
@@ -19,6 +38,7 @@ where
                 .has_headers(false)
                 .from_reader(reader),
             string_record: csv::StringRecord::new(),
+            path: None,
         }
     }
 
@@ -34,12 +54,49 @@ where
 {
     /// Read a Fai record
     fn read(&mut self, record: &mut Record) -> Result<()> {
+        self.read_record(record).map_err(|e| self.locate(e))
+    }
+}
+
+impl<R> Reader<R>
+where
+    R: std::io::Read,
+{
+    /// Read a Fai record without locating errors
+    fn read_record(&mut self, record: &mut Record) -> Result<()> {
         if self.reader.read_record(&mut self.string_record)? {
             *record = Record::try_from(&mut self.string_record)?;
             return Ok(());
         }
         Err(Error::new(ErrorKind::Eof, "end of file"))
     }
+
+    /// Add the location of the last record read to an error
+    fn locate(&self, error: Error) -> Error {
+        let mut error = match self.string_record.position() {
+            Some(position) => error
+                .with_line(position.line() as usize)
+                .with_offset(position.byte()),
+            None => error,
+        };
+        if error.kind != ErrorKind::Eof {
+            error = error.with_record(self.string_record.get(0).unwrap_or_default());
+        }
+        match &self.path {
+            Some(path) => error.with_path(path),
+            None => error,
+        }
+    }
+}
+
+impl Reader<std::fs::File> {
+    /// Construct a Fai reader from path
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let file = std::fs::File::open(&path).map_err(|e| Error::from(e).with_path(&path))?;
+        let mut reader = Self::new(file);
+        reader.path = Some(path.as_ref().to_path_buf());
+        Ok(reader)
+    }
 }
 
 #[cfg(test)]
@@ -66,6 +123,7 @@ mod tests {
                     line_bases: 3,
                     line_width: 4,
                     qual_offset: None,
+                    md5: None,
                 },
             },
             TestCase {
@@ -94,6 +152,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_reader_read_location() {
+        let input_lines: &[u8] = b"ref1\t1\t2\t3\t4\nref2\t1\tx\t3\t4\n";
+        let mut reader = Reader::new(input_lines);
+        let mut record = Record::new();
+        assert!(reader.read(&mut record).is_ok());
+        let error = reader.read(&mut record).unwrap_err();
+        assert_eq!(
+            (Some(2), Some(13), Some("ref2".to_string())),
+            (
+                error.location.line,
+                error.location.offset,
+                error.location.record
+            ),
+            "Should locate errors at the offending record",
+        );
+    }
+
     #[test]
     fn test_reader_read_different_widths() {
         let name = "Should error out if mixing fasta and fastq entries";
@@ -125,6 +201,7 @@ mod tests {
                 line_bases: 3,
                 line_width: 4,
                 qual_offset: Some(5),
+                md5: None,
             })),
             "Should iterate and return the first entry",
         );
@@ -137,6 +214,7 @@ mod tests {
                 line_bases: 3,
                 line_width: 4,
                 qual_offset: Some(5),
+                md5: None,
             })),
             "Should iterate and return the second entry",
         );
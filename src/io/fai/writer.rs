@@ -2,6 +2,27 @@ use super::{Record, DELIMITER};
 use crate::errors::Result;
 
 /// Writer is a writer for fai files
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::fai;
+///
+/// let mut output = Vec::new();
+/// let mut writer = fai::Writer::new(&mut output);
+/// writer.write(&fai::Record {
+///     name: "chr1".into(),
+///     length: 12,
+///     offset: 6,
+///     line_bases: 8,
+///     line_width: 9,
+///     ..fai::Record::new()
+/// })?;
+/// drop(writer);
+/// assert_eq!(b"chr1\t12\t6\t8\t9\n".to_vec(), output);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
 pub struct Writer<W: std::io::Write> {
     writer: csv::Writer<W>,
 }
@@ -25,6 +46,12 @@ where
         self.writer.write_record(&record.to_string_record())?;
         Ok(())
     }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        self.writer.flush()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -48,6 +75,7 @@ mod tests {
                     line_bases: 3,
                     line_width: 4,
                     qual_offset: None,
+                    md5: None,
                 },
                 expected: "record1\t1\t2\t3\t4\n",
             },
@@ -60,6 +88,7 @@ mod tests {
                     line_bases: 3,
                     line_width: 4,
                     qual_offset: Some(5),
+                    md5: None,
                 },
                 expected: "record2\t1\t2\t3\t4\t5\n",
             },
@@ -87,6 +116,7 @@ mod tests {
             line_bases: 3,
             line_width: 4,
             qual_offset: None,
+            md5: None,
         };
         assert!(writer.write(&record).is_ok(), "{}", name);
         record.qual_offset = Some(1);
@@ -0,0 +1,34 @@
+mod reader;
+mod writer;
+
+pub use reader::{Reader, Records};
+pub use writer::Writer;
+
+/// Default number of bases per line
+pub const DEFAULT_LINE_WIDTH: usize = 60;
+
+/// FASTA record
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Record {
+    /// Sequence name, which is the part of the description line up to the first whitespace unless
+    /// another [`NameSplit`](super::sequence::NameSplit) is used
+    pub name: String,
+    /// Rest of the description line, if any
+    pub description: Option<String>,
+    /// Bases with line terminators removed
+    pub sequence: Vec<u8>,
+}
+
+impl Record {
+    /// Construct an empty record
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clear the record, keeping its allocations
+    pub fn clear(&mut self) {
+        self.name.clear();
+        self.description = None;
+        self.sequence.clear();
+    }
+}
@@ -0,0 +1,267 @@
+use super::super::common::LineReader;
+use super::super::sequence::NameSplit;
+use super::super::source::Source;
+use super::Record;
+use crate::errors::{Error, ErrorKind, Result};
+
+const DESCRIPTION_PREFIX: u8 = b'>';
+
+/// Reader is a streaming reader for FASTA files
+///
+/// Blank lines are ignored.  Errors are located at the line, offset and record where they
+/// occurred, and at the input path if the reader was constructed with [`Reader::from_path`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::fasta;
+///
+/// let input: &[u8] = b">chr1 first\nACGT\nAC\n>chr2\nGGCC\n";
+/// let mut reader = fasta::Reader::new(input);
+/// while let Some(record) = reader.read_next() {
+///     let record = record?;
+///     assert!(record.name.starts_with("chr"));
+/// }
+///
+/// let records = fasta::Reader::new(input)
+///     .iter()
+///     .collect::<rust_samtools::errors::Result<Vec<_>>>()?;
+/// assert_eq!(b"ACGTAC".to_vec(), records[0].sequence);
+/// assert_eq!(Some("first".to_string()), records[0].description);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
+pub struct Reader<R>
+where
+    R: std::io::BufRead,
+{
+    lines: LineReader<R>,
+    name_split: NameSplit,
+    path: Option<std::path::PathBuf>,
+    record: Record,
+}
+
+impl<R> Reader<R>
+where
+    R: std::io::BufRead,
+{
+    /// Construct a FASTA reader from `std::io::BufRead`
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: LineReader::new(reader),
+            name_split: NameSplit::default(),
+            path: None,
+            record: Record::new(),
+        }
+    }
+
+    /// Set the rule for splitting names from descriptions
+    pub fn with_name_split(mut self, name_split: NameSplit) -> Self {
+        self.name_split = name_split;
+        self
+    }
+
+    /// Read the next record into `record`, reusing its allocations
+    ///
+    /// An Eof error is returned at the end of the input.
+    ///
+    pub fn read(&mut self, record: &mut Record) -> Result<()> {
+        record.clear();
+        self.read_record(record).map_err(|e| self.locate(e, record))
+    }
+
+    /// Read the next record into a buffer owned by the reader
+    ///
+    /// The returned record is borrowed until the next call, so no record is allocated per read.
+    /// `None` is returned at the end of the input.
+    ///
+    pub fn read_next(&mut self) -> Option<Result<&Record>> {
+        let mut record = std::mem::take(&mut self.record);
+        let result = self.read(&mut record);
+        self.record = record;
+        match result {
+            Ok(()) => Some(Ok(&self.record)),
+            Err(err) if err.kind == ErrorKind::Eof => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    /// Consume the reader by iterating over owned records
+    pub fn iter(self) -> Records<R> {
+        Records { reader: self }
+    }
+
+    /// Consume the reader and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.lines.into_inner()
+    }
+
+    /// Read a record without locating errors
+    ///
+    /// The description line of the next record is left in the line reader.
+    ///
+    fn read_record(&mut self, record: &mut Record) -> Result<()> {
+        while self.lines.trimmed().is_empty() {
+            if !self.lines.next_line()? {
+                return Err(Error::new(ErrorKind::Eof, "end of file"));
+            }
+        }
+        let line = self.lines.trimmed();
+        if line.first() != Some(&DESCRIPTION_PREFIX) {
+            return Err(Error::new(ErrorKind::Input, "invalid input format"));
+        }
+        let (name, description) = self.name_split.split(std::str::from_utf8(&line[1..])?);
+        record.name.push_str(name);
+        record.description = description.map(String::from);
+        while self.lines.next_line()? {
+            if self.lines.line().first() == Some(&DESCRIPTION_PREFIX) {
+                break;
+            }
+            record.sequence.extend_from_slice(self.lines.trimmed());
+        }
+        Ok(())
+    }
+
+    /// Add the location of the last line read to an error
+    fn locate(&self, error: Error, record: &Record) -> Error {
+        let error = self
+            .lines
+            .position()
+            .locate(error)
+            .with_record(&record.name);
+        match &self.path {
+            Some(path) => error.with_path(path),
+            None => error,
+        }
+    }
+}
+
+impl Reader<std::io::BufReader<Source>> {
+    /// Construct a FASTA reader from path
+    ///
+    /// BGZF compressed input is decompressed.
+    ///
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let mut reader = Self::new(std::io::BufReader::new(Source::from_path(&path)?));
+        reader.path = Some(path.as_ref().to_path_buf());
+        Ok(reader)
+    }
+}
+
+/// Records iterates over the owned records of a FASTA reader
+pub struct Records<R>
+where
+    R: std::io::BufRead,
+{
+    reader: Reader<R>,
+}
+
+impl<R> Records<R>
+where
+    R: std::io::BufRead,
+{
+    /// Consume the iterator and return the reader
+    pub fn into_inner(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+impl<R> Iterator for Records<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Record::new();
+        match self.reader.read(&mut record) {
+            Ok(()) => Some(Ok(record)),
+            Err(err) if err.kind == ErrorKind::Eof => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_read() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a [u8],
+            expected: Result<Vec<Record>>,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should read names, descriptions and sequences",
+                input: b">one desc\r\nACGT\r\nAC\r\n\r\n>two\n\n>three\nA\nC",
+                expected: Ok(vec![
+                    Record {
+                        name: "one".into(),
+                        description: Some("desc".into()),
+                        sequence: b"ACGTAC".to_vec(),
+                    },
+                    Record {
+                        name: "two".into(),
+                        description: None,
+                        sequence: vec![],
+                    },
+                    Record {
+                        name: "three".into(),
+                        description: None,
+                        sequence: b"AC".to_vec(),
+                    },
+                ]),
+            },
+            TestCase {
+                name: "Should read an empty input",
+                input: b"\n",
+                expected: Ok(vec![]),
+            },
+            TestCase {
+                name: "Should reject data before the first description line",
+                input: b"\nACGT\n>one\nACGT\n",
+                expected: Err(Error::new(ErrorKind::Input, "invalid input format")
+                    .with_line(2)
+                    .with_offset(1)),
+            },
+        ];
+        for test_case in test_cases {
+            let actual = Reader::new(test_case.input)
+                .iter()
+                .collect::<Result<Vec<_>>>();
+            assert_eq!(test_case.expected, actual, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_reader_name_split() {
+        let input: &[u8] = b">gi|568336023|chr1\nACGT\n";
+        let record = Reader::new(input)
+            .with_name_split(NameSplit::Delimiter('|'))
+            .iter()
+            .next()
+            .unwrap();
+        assert_eq!(
+            Ok(("gi".to_string(), Some("568336023|chr1".to_string()))),
+            record.map(|record| (record.name, record.description)),
+            "Should split the name with a custom delimiter",
+        );
+    }
+
+    #[test]
+    fn test_reader_read_next() {
+        let mut reader = Reader::new(&b">one\nACGT\n>two\nGG\n"[..]);
+        let mut names = Vec::new();
+        while let Some(record) = reader.read_next() {
+            names.push(record.unwrap().name.clone());
+        }
+        assert_eq!(
+            vec!["one", "two"],
+            names,
+            "Should borrow each record in turn"
+        );
+    }
+}
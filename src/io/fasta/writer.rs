@@ -0,0 +1,239 @@
+use super::super::common::{self, Counter};
+use super::super::fai;
+use super::Record;
+use crate::errors::{Error, ErrorKind, Result};
+
+const DESCRIPTION_PREFIX: &[u8] = b">";
+
+/// Writer is a writer for FASTA files
+///
+/// The writer can also build the Fai index of everything it writes, which saves indexing the
+/// output afterwards.
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::{fai, fasta};
+///
+/// let mut writer = fasta::Writer::new(Vec::new(), 4).with_index();
+/// writer.write("chr1", &b"ACGTACGTAC"[..])?;
+/// let mut index = Vec::new();
+/// writer.write_index(fai::Writer::new(&mut index))?;
+/// assert_eq!(b">chr1\nACGT\nACGT\nAC\n".to_vec(), writer.into_inner());
+/// assert_eq!(b"chr1\t10\t6\t4\t5\n".to_vec(), index);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
+pub struct Writer<W: std::io::Write> {
+    writer: Counter<W>,
+    line_width: usize,
+    index: Option<Vec<fai::Record>>,
+}
+
+impl<W> Writer<W>
+where
+    W: std::io::Write,
+{
+    /// Construct a FASTA writer from `std::io::Write`
+    ///
+    /// Sequences are wrapped every `line_width` bases.  A `line_width` of 0 disables wrapping.
+    ///
+    pub fn new(writer: W, line_width: usize) -> Self {
+        Self {
+            writer: Counter::new(writer),
+            line_width,
+            index: None,
+        }
+    }
+
+    /// Build the Fai index of the written entries
+    ///
+    /// Offsets are counted from the first byte written by this writer.
+    ///
+    pub fn with_index(mut self) -> Self {
+        self.index = Some(Vec::new());
+        self
+    }
+
+    /// Write a FASTA entry, streaming its sequence from `sequence`
+    pub fn write<R>(&mut self, name: &str, sequence: R) -> Result<()>
+    where
+        R: std::io::Read,
+    {
+        self.write_entry(name, None, sequence)
+    }
+
+    /// Write a FASTA record, including its description
+    pub fn write_record(&mut self, record: &Record) -> Result<()> {
+        self.write_entry(
+            &record.name,
+            record.description.as_deref(),
+            &record.sequence[..],
+        )
+    }
+
+    /// Fai records of the entries written so far, if the index is enabled
+    pub fn index(&self) -> Option<&[fai::Record]> {
+        self.index.as_deref()
+    }
+
+    /// Write the Fai records of the entries written so far
+    ///
+    /// An error is returned if the index was not enabled with [`Writer::with_index`].
+    ///
+    pub fn write_index<I>(&self, mut writer: fai::Writer<I>) -> Result<()>
+    where
+        I: std::io::Write,
+    {
+        let index = self.index().ok_or_else(|| {
+            Error::new(ErrorKind::User, "the FASTA writer does not build an index")
+        })?;
+        for record in index {
+            writer.write(record)?;
+        }
+        writer.flush()
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        std::io::Write::flush(&mut self.writer)?;
+        Ok(())
+    }
+
+    /// Get a mutable reference to the underlying writer
+    ///
+    /// Data written directly is not accounted for in the index offsets.
+    ///
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    /// Consume the FASTA writer and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    /// Write a description line and the wrapped sequence
+    fn write_entry<R>(&mut self, name: &str, description: Option<&str>, sequence: R) -> Result<()>
+    where
+        R: std::io::Read,
+    {
+        common::write_description(&mut self.writer, DESCRIPTION_PREFIX, name, description)?;
+        let offset = self.writer.count();
+        let length = common::write_wrapped(&mut self.writer, sequence, self.line_width)?;
+        if let Some(index) = self.index.as_mut() {
+            index.push(fai::Record::wrapped(
+                name,
+                length as usize,
+                offset,
+                self.line_width,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writer_write() {
+        struct TestCase<'a> {
+            name: &'a str,
+            line_width: usize,
+            sequence: &'a [u8],
+            expected: &'a str,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should wrap the sequence",
+                line_width: 4,
+                sequence: b"ATGCATGCAT",
+                expected: ">seq\nATGC\nATGC\nAT\n",
+            },
+            TestCase {
+                name: "Should not add an empty line when the sequence fills the last line",
+                line_width: 5,
+                sequence: b"ATGCATGCAT",
+                expected: ">seq\nATGCA\nTGCAT\n",
+            },
+            TestCase {
+                name: "Should not wrap with a line width of 0",
+                line_width: 0,
+                sequence: b"ATGCATGCAT",
+                expected: ">seq\nATGCATGCAT\n",
+            },
+            TestCase {
+                name: "Should write an empty sequence",
+                line_width: 4,
+                sequence: b"",
+                expected: ">seq\n",
+            },
+        ];
+        for test_case in test_cases {
+            let mut writer = Writer::new(vec![], test_case.line_width);
+            assert!(
+                writer.write("seq", test_case.sequence).is_ok(),
+                "{}",
+                test_case.name
+            );
+            let data = String::from_utf8(writer.into_inner()).unwrap();
+            assert_eq!(test_case.expected, &data, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_writer_write_record() {
+        let mut writer = Writer::new(vec![], 4);
+        let record = Record {
+            name: "seq".into(),
+            description: Some("some description".into()),
+            sequence: b"ACGTAC".to_vec(),
+        };
+        assert!(writer.write_record(&record).is_ok());
+        assert_eq!(
+            b">seq some description\nACGT\nAC\n".to_vec(),
+            writer.into_inner(),
+            "Should write the description",
+        );
+        assert!(
+            Writer::new(vec![], 4)
+                .write_index(fai::Writer::new(vec![]))
+                .is_err(),
+            "Should return an error if the index is not enabled",
+        );
+    }
+
+    #[test]
+    fn test_writer_index() {
+        let sequences: [(&str, &[u8]); 4] = [
+            ("one", b"ACGTACGTAC"),
+            ("two", b""),
+            ("three", b"ACG"),
+            ("four", b"ACGTACGT"),
+        ];
+        for line_width in [0, 4] {
+            let mut writer = Writer::new(vec![], line_width).with_index();
+            for (name, sequence) in sequences {
+                let record = Record {
+                    name: name.to_string(),
+                    description: Some("desc".into()),
+                    sequence: sequence.to_vec(),
+                };
+                assert!(writer.write_record(&record).is_ok());
+            }
+            let expected = writer.index().unwrap().to_vec();
+            let output = writer.into_inner();
+            let actual = fai::Indexer::new(std::io::Cursor::new(output), fai::IndexerFormat::FASTA)
+                .iter()
+                .collect::<Result<Vec<_>>>();
+            assert_eq!(
+                Ok(expected),
+                actual,
+                "Should build the same index as the indexer with a line width of {}",
+                line_width
+            );
+        }
+    }
+}
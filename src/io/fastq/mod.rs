@@ -0,0 +1,156 @@
+mod reader;
+mod writer;
+
+use super::sequence::NameSplit;
+use crate::errors::{Error, ErrorKind, Result};
+
+pub use reader::{Reader, Records};
+pub use writer::Writer;
+
+const STRICT: &str = "strict";
+const LENIENT: &str = "lenient";
+
+/// FASTQ record
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Record {
+    /// Read name, which is the part of the description line up to the first whitespace unless
+    /// another [`NameSplit`](super::sequence::NameSplit) is used
+    pub name: String,
+    /// Rest of the description line, if any
+    pub description: Option<String>,
+    /// Bases with line terminators removed
+    pub sequence: Vec<u8>,
+    /// Qualities with line terminators removed, one per base
+    pub quality: Vec<u8>,
+}
+
+impl Record {
+    /// Construct an empty record
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Clear the record, keeping its allocations
+    pub fn clear(&mut self) {
+        self.name.clear();
+        self.description = None;
+        self.sequence.clear();
+        self.quality.clear();
+    }
+}
+
+/// Strictness controls how FASTQ entries are validated
+///
+/// The quality length must always match the sequence length, since the qualities can't be
+/// located otherwise.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Strictness {
+    /// A name on the `+` line must match the name on the header line
+    #[default]
+    Strict,
+    /// Anything after the `+` is ignored
+    Lenient,
+}
+
+impl Strictness {
+    /// Check the `+` line of a record named `name`
+    ///
+    /// `line` starts with the `+` and may end with its line terminator.  With
+    /// [`Strictness::Strict`], a name after the `+`, split with `name_split` like the header line,
+    /// must match `name`.
+    ///
+    pub fn check_separator(&self, line: &[u8], name: &str, name_split: NameSplit) -> Result<()> {
+        if *self == Self::Lenient {
+            return Ok(());
+        }
+        let line = std::str::from_utf8(line.get(1..).unwrap_or_default())?;
+        let (separator_name, _) = name_split.split(line);
+        if !separator_name.is_empty() && separator_name != name {
+            return Err(Error::new(
+                ErrorKind::Input,
+                &format!(
+                    "'+' line name {} does not match header name",
+                    separator_name
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Strictness {
+    type Err = Error;
+
+    /// Parse a strictness from `strict` or `lenient`
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            STRICT => Ok(Self::Strict),
+            LENIENT => Ok(Self::Lenient),
+            _ => Err(Error::new(
+                ErrorKind::User,
+                &format!("invalid strictness: {}", s),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_separator() {
+        struct TestCase<'a> {
+            name: &'a str,
+            line: &'a [u8],
+            strictness: Strictness,
+            expect_error: bool,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should accept a bare separator",
+                line: b"+\n",
+                strictness: Strictness::Strict,
+                expect_error: false,
+            },
+            TestCase {
+                name: "Should accept a matching name",
+                line: b"+read1\r\n",
+                strictness: Strictness::Strict,
+                expect_error: false,
+            },
+            TestCase {
+                name: "Should accept a matching name with a description",
+                line: b"+read1 description\n",
+                strictness: Strictness::Strict,
+                expect_error: false,
+            },
+            TestCase {
+                name: "Should reject a different name",
+                line: b"+read2\n",
+                strictness: Strictness::Strict,
+                expect_error: true,
+            },
+            TestCase {
+                name: "Should ignore a different name when lenient",
+                line: b"+read2\n",
+                strictness: Strictness::Lenient,
+                expect_error: false,
+            },
+        ];
+        for test_case in test_cases {
+            let actual = test_case.strictness.check_separator(
+                test_case.line,
+                "read1",
+                NameSplit::Whitespace,
+            );
+            assert_eq!(
+                test_case.expect_error,
+                actual.is_err(),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+}
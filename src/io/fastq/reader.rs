@@ -0,0 +1,348 @@
+use super::super::common::LineReader;
+use super::super::sequence::NameSplit;
+use super::super::source::Source;
+use super::{Record, Strictness};
+use crate::errors::{Error, ErrorKind, Result};
+
+const DESCRIPTION_PREFIX: u8 = b'@';
+const SEPARATOR_PREFIX: u8 = b'+';
+
+/// Reader is a streaming reader for FASTQ files
+///
+/// Sequences and qualities may be wrapped over several lines.  Qualities are read until there
+/// are as many as bases, so quality lines starting with `@` or `+` are handled.  Blank lines
+/// between records are ignored.  Errors are located at the line, offset and record where they
+/// occurred, and at the input path if the reader was constructed with [`Reader::from_path`].
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::fastq;
+///
+/// let input: &[u8] = b"@read1 lane1\nACGT\n+\n@@II\n@read2\nGG\n+\nHH\n";
+/// let mut reader = fastq::Reader::new(input);
+/// while let Some(record) = reader.read_next() {
+///     let record = record?;
+///     assert_eq!(record.sequence.len(), record.quality.len());
+/// }
+///
+/// let records = fastq::Reader::new(input)
+///     .iter()
+///     .collect::<rust_samtools::errors::Result<Vec<_>>>()?;
+/// assert_eq!(b"@@II".to_vec(), records[0].quality);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
+pub struct Reader<R>
+where
+    R: std::io::BufRead,
+{
+    lines: LineReader<R>,
+    strictness: Strictness,
+    name_split: NameSplit,
+    path: Option<std::path::PathBuf>,
+    record: Record,
+}
+
+impl<R> Reader<R>
+where
+    R: std::io::BufRead,
+{
+    /// Construct a FASTQ reader from `std::io::BufRead`
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: LineReader::new(reader),
+            strictness: Strictness::default(),
+            name_split: NameSplit::default(),
+            path: None,
+            record: Record::new(),
+        }
+    }
+
+    /// Set how strictly records are validated
+    pub fn with_strictness(mut self, strictness: Strictness) -> Self {
+        self.strictness = strictness;
+        self
+    }
+
+    /// Set the rule for splitting names from descriptions
+    pub fn with_name_split(mut self, name_split: NameSplit) -> Self {
+        self.name_split = name_split;
+        self
+    }
+
+    /// Read the next record into `record`, reusing its allocations
+    ///
+    /// An Eof error is returned at the end of the input.
+    ///
+    pub fn read(&mut self, record: &mut Record) -> Result<()> {
+        record.clear();
+        self.read_record(record).map_err(|e| self.locate(e, record))
+    }
+
+    /// Read the next record into a buffer owned by the reader
+    ///
+    /// The returned record is borrowed until the next call, so no record is allocated per read.
+    /// `None` is returned at the end of the input.
+    ///
+    pub fn read_next(&mut self) -> Option<Result<&Record>> {
+        let mut record = std::mem::take(&mut self.record);
+        let result = self.read(&mut record);
+        self.record = record;
+        match result {
+            Ok(()) => Some(Ok(&self.record)),
+            Err(err) if err.kind == ErrorKind::Eof => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    /// Consume the reader by iterating over owned records
+    pub fn iter(self) -> Records<R> {
+        Records { reader: self }
+    }
+
+    /// Consume the reader and return the underlying reader
+    pub fn into_inner(self) -> R {
+        self.lines.into_inner()
+    }
+
+    /// Read a record without locating errors
+    fn read_record(&mut self, record: &mut Record) -> Result<()> {
+        self.read_description(record)?;
+        self.read_sequence(record)?;
+        self.read_separator(record)?;
+        self.read_quality(record)
+    }
+
+    /// Read the description line, skipping blank lines before it
+    fn read_description(&mut self, record: &mut Record) -> Result<()> {
+        loop {
+            if !self.lines.next_line()? {
+                return Err(Error::new(ErrorKind::Eof, "end of file"));
+            }
+            if !self.lines.trimmed().is_empty() {
+                break;
+            }
+        }
+        let line = self.lines.trimmed();
+        if line.first() != Some(&DESCRIPTION_PREFIX) {
+            return Err(Error::new(ErrorKind::Input, "invalid input format"));
+        }
+        let (name, description) = self.name_split.split(std::str::from_utf8(&line[1..])?);
+        record.name.push_str(name);
+        record.description = description.map(String::from);
+        Ok(())
+    }
+
+    /// Read sequence lines up to the `+` line
+    fn read_sequence(&mut self, record: &mut Record) -> Result<()> {
+        loop {
+            if !self.lines.next_line()? {
+                return Err(self
+                    .lines
+                    .position()
+                    .locate_next(Error::new(ErrorKind::Input, "missing '+' line")));
+            }
+            if self.lines.line().first() == Some(&SEPARATOR_PREFIX) {
+                return Ok(());
+            }
+            record.sequence.extend_from_slice(self.lines.trimmed());
+        }
+    }
+
+    /// Check the name on the `+` line, as described in [`Strictness::check_separator`]
+    fn read_separator(&mut self, record: &Record) -> Result<()> {
+        self.strictness
+            .check_separator(self.lines.line(), &record.name, self.name_split)
+    }
+
+    /// Read quality lines until there are as many qualities as bases
+    fn read_quality(&mut self, record: &mut Record) -> Result<()> {
+        while record.quality.len() < record.sequence.len() {
+            if !self.lines.next_line()? {
+                return Err(self.lines.position().locate_next(Error::new(
+                    ErrorKind::Input,
+                    &format!(
+                        "quality length does not match sequence length: found {} qualities, \
+                         expected {}",
+                        record.quality.len(),
+                        record.sequence.len()
+                    ),
+                )));
+            }
+            let line = self.lines.trimmed();
+            if line.is_empty() {
+                return Err(Error::new(ErrorKind::Input, "blank line inside quality"));
+            }
+            record.quality.extend_from_slice(line);
+        }
+        if record.quality.len() > record.sequence.len() {
+            return Err(Error::new(
+                ErrorKind::Input,
+                &format!(
+                    "quality length does not match sequence length: found at least {} \
+                     qualities, expected {}",
+                    record.quality.len(),
+                    record.sequence.len()
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Add the location of the last line read to an error
+    fn locate(&self, error: Error, record: &Record) -> Error {
+        let error = self
+            .lines
+            .position()
+            .locate(error)
+            .with_record(&record.name);
+        match &self.path {
+            Some(path) => error.with_path(path),
+            None => error,
+        }
+    }
+}
+
+impl Reader<std::io::BufReader<Source>> {
+    /// Construct a FASTQ reader from path
+    ///
+    /// BGZF compressed input is decompressed.
+    ///
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        let mut reader = Self::new(std::io::BufReader::new(Source::from_path(&path)?));
+        reader.path = Some(path.as_ref().to_path_buf());
+        Ok(reader)
+    }
+}
+
+/// Records iterates over the owned records of a FASTQ reader
+pub struct Records<R>
+where
+    R: std::io::BufRead,
+{
+    reader: Reader<R>,
+}
+
+impl<R> Records<R>
+where
+    R: std::io::BufRead,
+{
+    /// Consume the iterator and return the reader
+    pub fn into_inner(self) -> Reader<R> {
+        self.reader
+    }
+}
+
+impl<R> Iterator for Records<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut record = Record::new();
+        match self.reader.read(&mut record) {
+            Ok(()) => Some(Ok(record)),
+            Err(err) if err.kind == ErrorKind::Eof => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_read() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a [u8],
+            expected: Result<Vec<Record>>,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should read wrapped sequences and qualities",
+                input: b"@r1 desc\nACGT\nAC\n+r1\n@@+@\n+@\n\n@r2\n\n+\n\n",
+                expected: Ok(vec![
+                    Record {
+                        name: "r1".into(),
+                        description: Some("desc".into()),
+                        sequence: b"ACGTAC".to_vec(),
+                        quality: b"@@+@+@".to_vec(),
+                    },
+                    Record {
+                        name: "r2".into(),
+                        description: None,
+                        sequence: vec![],
+                        quality: vec![],
+                    },
+                ]),
+            },
+            TestCase {
+                name: "Should reject truncated qualities",
+                input: b"@r1\nACGT\n+\nII\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "quality length does not match sequence length: found 2 qualities, \
+                     expected 4",
+                )
+                .with_line(5)
+                .with_offset(14)
+                .with_record("r1")),
+            },
+            TestCase {
+                name: "Should reject too many qualities",
+                input: b"@r1\nACGT\n+\nIIIII\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "quality length does not match sequence length: found at least 5 \
+                     qualities, expected 4",
+                )
+                .with_line(4)
+                .with_offset(11)
+                .with_record("r1")),
+            },
+            TestCase {
+                name: "Should reject a + line with a different name",
+                input: b"@r1\nACGT\n+r2\nIIII\n",
+                expected: Err(Error::new(
+                    ErrorKind::Input,
+                    "'+' line name r2 does not match header name",
+                )
+                .with_line(3)
+                .with_offset(9)
+                .with_record("r1")),
+            },
+            TestCase {
+                name: "Should reject a missing + line",
+                input: b"@r1\nACGT\n",
+                expected: Err(Error::new(ErrorKind::Input, "missing '+' line")
+                    .with_line(3)
+                    .with_offset(9)
+                    .with_record("r1")),
+            },
+        ];
+        for test_case in test_cases {
+            let actual = Reader::new(test_case.input)
+                .iter()
+                .collect::<Result<Vec<_>>>();
+            assert_eq!(test_case.expected, actual, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_reader_strictness() {
+        let input: &[u8] = b"@r1\nACGT\n+r2\nIIII\n";
+        let mut reader = Reader::new(input).with_strictness(Strictness::Lenient);
+        assert_eq!(
+            Some("r1".to_string()),
+            reader
+                .read_next()
+                .map(|record| record.unwrap().name.clone()),
+            "Should ignore the + line name when lenient",
+        );
+        assert!(reader.read_next().is_none(), "Should reach the end");
+    }
+}
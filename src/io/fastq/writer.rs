@@ -0,0 +1,281 @@
+use super::super::common::{self, Counter};
+use super::super::fai;
+use super::Record;
+use crate::errors::{Error, ErrorKind, Result};
+
+const DESCRIPTION_PREFIX: &[u8] = b"@";
+const SEPARATOR: &[u8] = b"+";
+
+/// Writer is a writer for FASTQ files
+///
+/// The writer can also build the Fai index of everything it writes, which saves indexing the
+/// output afterwards.
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::fastq;
+///
+/// let mut writer = fastq::Writer::new(Vec::new(), 4).with_index();
+/// writer.write("read1", &b"ACGTAC"[..], &b"IIIIHH"[..])?;
+/// assert_eq!(Some(17), writer.index().unwrap()[0].qual_offset);
+/// assert_eq!(
+///     b"@read1\nACGT\nAC\n+\nIIII\nHH\n".to_vec(),
+///     writer.into_inner()
+/// );
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
+pub struct Writer<W: std::io::Write> {
+    writer: Counter<W>,
+    line_width: usize,
+    index: Option<Vec<fai::Record>>,
+}
+
+impl<W> Writer<W>
+where
+    W: std::io::Write,
+{
+    /// Construct a FASTQ writer from `std::io::Write`
+    ///
+    /// Sequences and qualities are wrapped every `line_width` bytes.  A `line_width` of 0
+    /// disables wrapping.
+    ///
+    pub fn new(writer: W, line_width: usize) -> Self {
+        Self {
+            writer: Counter::new(writer),
+            line_width,
+            index: None,
+        }
+    }
+
+    /// Build the Fai index of the written entries
+    ///
+    /// Offsets are counted from the first byte written by this writer.
+    ///
+    pub fn with_index(mut self) -> Self {
+        self.index = Some(Vec::new());
+        self
+    }
+
+    /// Write a FASTQ entry, streaming its sequence and quality
+    ///
+    /// The lengths are only known once both have been copied, so if the quality length does not
+    /// match the sequence length, an error is returned after the whole entry has been written.
+    /// The output then holds the malformed entry, which is left out of the index.
+    ///
+    pub fn write<S, Q>(&mut self, name: &str, sequence: S, quality: Q) -> Result<()>
+    where
+        S: std::io::Read,
+        Q: std::io::Read,
+    {
+        self.write_entry(name, None, sequence, quality)
+    }
+
+    /// Write a FASTQ record, including its description
+    ///
+    /// Nothing is written if the quality length does not match the sequence length.
+    ///
+    pub fn write_record(&mut self, record: &Record) -> Result<()> {
+        if record.quality.len() != record.sequence.len() {
+            return Err(quality_length_error(
+                &record.name,
+                record.quality.len() as u64,
+                record.sequence.len() as u64,
+            ));
+        }
+        self.write_entry(
+            &record.name,
+            record.description.as_deref(),
+            &record.sequence[..],
+            &record.quality[..],
+        )
+    }
+
+    /// Fai records of the entries written so far, if the index is enabled
+    pub fn index(&self) -> Option<&[fai::Record]> {
+        self.index.as_deref()
+    }
+
+    /// Write the Fai records of the entries written so far
+    ///
+    /// An error is returned if the index was not enabled with [`Writer::with_index`].
+    ///
+    pub fn write_index<I>(&self, mut writer: fai::Writer<I>) -> Result<()>
+    where
+        I: std::io::Write,
+    {
+        let index = self.index().ok_or_else(|| {
+            Error::new(ErrorKind::User, "the FASTQ writer does not build an index")
+        })?;
+        for record in index {
+            writer.write(record)?;
+        }
+        writer.flush()
+    }
+
+    /// Flush the underlying writer
+    pub fn flush(&mut self) -> Result<()> {
+        std::io::Write::flush(&mut self.writer)?;
+        Ok(())
+    }
+
+    /// Get a mutable reference to the underlying writer
+    ///
+    /// Data written directly is not accounted for in the index offsets.
+    ///
+    pub fn get_mut(&mut self) -> &mut W {
+        self.writer.get_mut()
+    }
+
+    /// Consume the FASTQ writer and return the underlying writer
+    pub fn into_inner(self) -> W {
+        self.writer.into_inner()
+    }
+
+    /// Write a description line, the wrapped sequence, a separator line and the wrapped quality
+    fn write_entry<S, Q>(
+        &mut self,
+        name: &str,
+        description: Option<&str>,
+        sequence: S,
+        quality: Q,
+    ) -> Result<()>
+    where
+        S: std::io::Read,
+        Q: std::io::Read,
+    {
+        common::write_description(&mut self.writer, DESCRIPTION_PREFIX, name, description)?;
+        let offset = self.writer.count();
+        let length = common::write_wrapped(&mut self.writer, sequence, self.line_width)?;
+        common::write_description(&mut self.writer, SEPARATOR, "", None)?;
+        let qual_offset = self.writer.count();
+        let quality_length = common::write_wrapped(&mut self.writer, quality, self.line_width)?;
+        if quality_length != length {
+            return Err(quality_length_error(name, quality_length, length));
+        }
+        if let Some(index) = self.index.as_mut() {
+            index.push(fai::Record {
+                qual_offset: Some(qual_offset),
+                ..fai::Record::wrapped(name, length as usize, offset, self.line_width)
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Error for an entry whose quality length does not match its sequence length
+fn quality_length_error(name: &str, found: u64, expected: u64) -> Error {
+    Error::new(
+        ErrorKind::Input,
+        &format!(
+            "quality length does not match sequence length: found {} qualities, expected {}",
+            found, expected
+        ),
+    )
+    .with_record(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_writer_write() {
+        struct TestCase<'a> {
+            name: &'a str,
+            line_width: usize,
+            sequence: &'a [u8],
+            quality: &'a [u8],
+            expected: &'a str,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should wrap the sequence and quality",
+                line_width: 4,
+                sequence: b"ATGCAT",
+                quality: b"FFFA@@",
+                expected: "@read\nATGC\nAT\n+\nFFFA\n@@\n",
+            },
+            TestCase {
+                name: "Should not wrap with a line width of 0",
+                line_width: 0,
+                sequence: b"ATGCAT",
+                quality: b"FFFA@@",
+                expected: "@read\nATGCAT\n+\nFFFA@@\n",
+            },
+        ];
+        for test_case in test_cases {
+            let mut writer = Writer::new(vec![], test_case.line_width);
+            assert!(
+                writer
+                    .write("read", test_case.sequence, test_case.quality)
+                    .is_ok(),
+                "{}",
+                test_case.name
+            );
+            let data = String::from_utf8(writer.into_inner()).unwrap();
+            assert_eq!(test_case.expected, &data, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_writer_quality_length() {
+        let mut writer = Writer::new(vec![], 4);
+        assert!(
+            writer.write("read", &b"ACGT"[..], &b"III"[..]).is_err(),
+            "Should return an error if the quality length does not match",
+        );
+
+        let mut writer = Writer::new(vec![], 4).with_index();
+        let record = Record {
+            name: "read".into(),
+            description: None,
+            sequence: b"ACGT".to_vec(),
+            quality: b"III".to_vec(),
+        };
+        assert_eq!(
+            Err(quality_length_error("read", 3, 4)),
+            writer.write_record(&record),
+            "Should return an error if the record quality length does not match",
+        );
+        assert_eq!(Some(&[][..]), writer.index(), "Should not index the record");
+        assert!(
+            writer.into_inner().is_empty(),
+            "Should not write the record"
+        );
+    }
+
+    #[test]
+    fn test_writer_index() {
+        let sequences: [(&str, &[u8]); 4] = [
+            ("one", b"ACGTACGTAC"),
+            ("two", b""),
+            ("three", b"ACG"),
+            ("four", b"ACGTACGT"),
+        ];
+        for line_width in [0, 4] {
+            let mut writer = Writer::new(vec![], line_width).with_index();
+            for (name, sequence) in sequences {
+                let record = Record {
+                    name: name.to_string(),
+                    description: Some("desc".into()),
+                    sequence: sequence.to_vec(),
+                    quality: sequence.to_vec(),
+                };
+                assert!(writer.write_record(&record).is_ok());
+            }
+            let expected = writer.index().unwrap().to_vec();
+            let output = writer.into_inner();
+            let actual = fai::Indexer::new(std::io::Cursor::new(output), fai::IndexerFormat::FASTQ)
+                .iter()
+                .collect::<Result<Vec<_>>>();
+            assert_eq!(
+                Ok(expected),
+                actual,
+                "Should build the same index as the indexer with a line width of {}",
+                line_width
+            );
+        }
+    }
+}
@@ -1,3 +1,11 @@
+pub mod bgzf;
+pub mod compression;
+pub mod dict;
 pub mod fai;
+pub mod fasta;
+pub mod fastq;
+pub mod region;
+pub mod sequence;
+pub mod source;
 
 mod common;
@@ -0,0 +1,491 @@
+use crate::errors::{Error, ErrorKind, Result};
+
+const BED_DELIMITER: char = '\t';
+const BED_HEADERS: [&str; 3] = ["#", "track ", "browser "];
+const NAME_SEPARATOR: char = ':';
+const RANGE_SEPARATOR: char = '-';
+const THOUSANDS_SEPARATOR: char = ',';
+
+/// Region of a reference sequence in samtools syntax
+///
+/// A region is written as `name[:start[-end]]` where `start` and `end` are 1-based and
+/// inclusive.  A missing `start` means the beginning of the sequence and a missing `end` means
+/// the end of the sequence.
+///
+/// # Examples
+///
+/// ```
+/// use rust_samtools::io::region::Region;
+///
+/// let region: Region = "chr1:1,001-2,000".parse()?;
+/// assert_eq!("chr1", region.name);
+/// assert_eq!((1000, 2000), region.interval(5000)?);
+/// # Ok::<(), rust_samtools::errors::Error>(())
+/// ```
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Region {
+    /// Name of the reference sequence
+    pub name: String,
+    /// 1-based start position, inclusive
+    pub start: Option<usize>,
+    /// 1-based end position, inclusive
+    pub end: Option<usize>,
+}
+
+impl Region {
+    /// Construct a region spanning an entire sequence
+    pub fn whole(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Construct a region from a BED-style 0-based half open interval
+    ///
+    /// An empty interval has an `end` one less than its `start`.
+    ///
+    pub fn from_bed(name: &str, start: usize, end: usize) -> Self {
+        Self {
+            name: name.into(),
+            start: Some(start + 1),
+            end: Some(end),
+        }
+    }
+
+    /// Convert the region to a 0-based half open interval
+    ///
+    /// The interval is clamped to `length`, the length of the sequence.  A `start` of 0 is taken
+    /// as 1.  An error is returned if the region starts more than one position after its end; a
+    /// region that ends just before it starts is empty.
+    ///
+    pub fn interval(&self, length: usize) -> Result<(usize, usize)> {
+        let start = self.start.map_or(0, |start| start.saturating_sub(1));
+        if let Some(end) = self.end {
+            if start > end {
+                return Err(Error::new(
+                    ErrorKind::User,
+                    &format!("invalid region {}: start is after end", self),
+                ));
+            }
+        }
+        let start = start.min(length);
+        let end = self.end.map_or(length, |end| end.min(length));
+        Ok((start, end.max(start)))
+    }
+}
+
+impl std::fmt::Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        match (self.start, self.end) {
+            (Some(start), Some(end)) => write!(f, ":{}-{}", start, end),
+            (Some(start), None) => write!(f, ":{}", start),
+            (None, Some(end)) => write!(f, ":1-{}", end),
+            (None, None) => Ok(()),
+        }
+    }
+}
+
+impl std::str::FromStr for Region {
+    type Err = Error;
+
+    /// Parse a region from a string
+    ///
+    /// If the text after the last `:` is not a valid range, the whole string is treated as the
+    /// sequence name.
+    ///
+    fn from_str(s: &str) -> Result<Self> {
+        if s.is_empty() {
+            return Err(Error::new(ErrorKind::User, "empty region"));
+        }
+        let (name, range) = match s.rsplit_once(NAME_SEPARATOR) {
+            Some((name, range)) if !name.is_empty() => (name, range),
+            _ => return Ok(Self::whole(s)),
+        };
+        match parse_range(range) {
+            Some((start, end)) => Ok(Self {
+                name: name.into(),
+                start,
+                end,
+            }),
+            None => Ok(Self::whole(s)),
+        }
+    }
+}
+
+/// Entry is a region read from a region file
+#[derive(Clone, Debug, PartialEq)]
+pub enum Entry {
+    /// Region in samtools syntax, left unparsed so it can be matched against sequence names first
+    Text(String),
+    /// Region from a BED-style line
+    Bed(Region),
+}
+
+impl std::fmt::Display for Entry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Text(text) => write!(f, "{}", text),
+            Self::Bed(region) => write!(f, "{}", region),
+        }
+    }
+}
+
+/// Reader reads regions from a file, one per line
+///
+/// Each line is either a region in samtools syntax or a BED-style line with a tab separated name,
+/// 0-based start and end.  Empty lines, comments and BED headers are skipped.
+///
+pub struct Reader<R>
+where
+    R: std::io::BufRead,
+{
+    reader: R,
+    buffer: String,
+}
+
+impl<R> Reader<R>
+where
+    R: std::io::BufRead,
+{
+    /// Construct a region reader from `std::io::BufRead`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buffer: String::new(),
+        }
+    }
+
+    /// Read the next region
+    ///
+    /// `None` is returned at the end of the file.
+    ///
+    pub fn read(&mut self) -> Result<Option<Entry>> {
+        loop {
+            self.buffer.clear();
+            if self.reader.read_line(&mut self.buffer)? == 0 {
+                return Ok(None);
+            }
+            if let Some(region) = parse_line(&self.buffer)? {
+                return Ok(Some(region));
+            }
+        }
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: std::io::BufRead,
+{
+    type Item = Result<Entry>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read().transpose()
+    }
+}
+
+/// Parse a line of a region file
+///
+/// Returns `None` for lines that hold no region.
+///
+fn parse_line(line: &str) -> Result<Option<Entry>> {
+    let line = line.trim_end_matches(&['\r', '\n'][..]);
+    if line.trim().is_empty() || BED_HEADERS.iter().any(|header| line.starts_with(header)) {
+        return Ok(None);
+    }
+    if !line.contains(BED_DELIMITER) {
+        return Ok(Some(Entry::Text(line.into())));
+    }
+    let fields: Vec<&str> = line.split(BED_DELIMITER).collect();
+    let invalid = || Error::new(ErrorKind::Input, &format!("invalid BED region: {}", line));
+    if fields.len() < 3 {
+        return Err(invalid());
+    }
+    let start: usize = fields[1].trim().parse().map_err(|_| invalid())?;
+    let end: usize = fields[2].trim().parse().map_err(|_| invalid())?;
+    if start > end {
+        return Err(invalid());
+    }
+    Ok(Some(Entry::Bed(Region::from_bed(fields[0], start, end))))
+}
+
+/// Parse the `start[-end]` part of a region
+///
+/// Returns `None` if the range is not well formed.
+///
+fn parse_range(range: &str) -> Option<(Option<usize>, Option<usize>)> {
+    let (start, end) = match range.split_once(RANGE_SEPARATOR) {
+        Some((start, end)) => (start, Some(end)),
+        None => (range, None),
+    };
+    let start = match parse_position(start)? {
+        Some(0) => return None,
+        start => start,
+    };
+    let end = match end {
+        Some(end) => parse_position(end)?,
+        None => None,
+    };
+    if start.is_none() && end.is_none() {
+        return None;
+    }
+    Some((start, end))
+}
+
+/// Parse a position, allowing thousands separators
+///
+/// Returns `Some(None)` for an empty position and `None` if the position is not a number.
+///
+fn parse_position(position: &str) -> Option<Option<usize>> {
+    let position = position.replace(THOUSANDS_SEPARATOR, "");
+    if position.is_empty() {
+        return Some(None);
+    }
+    position.parse().ok().map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_from_str() {
+        struct TestCase<'a> {
+            name: &'a str,
+            input: &'a str,
+            expect_error: bool,
+            expected: Region,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should parse a sequence name",
+                input: "chr1",
+                expect_error: false,
+                expected: Region::whole("chr1"),
+            },
+            TestCase {
+                name: "Should parse a start and an end",
+                input: "chr1:100-200",
+                expect_error: false,
+                expected: Region {
+                    name: "chr1".into(),
+                    start: Some(100),
+                    end: Some(200),
+                },
+            },
+            TestCase {
+                name: "Should parse a start without an end",
+                input: "chr1:100",
+                expect_error: false,
+                expected: Region {
+                    name: "chr1".into(),
+                    start: Some(100),
+                    end: None,
+                },
+            },
+            TestCase {
+                name: "Should parse an open ended range",
+                input: "chr1:100-",
+                expect_error: false,
+                expected: Region {
+                    name: "chr1".into(),
+                    start: Some(100),
+                    end: None,
+                },
+            },
+            TestCase {
+                name: "Should parse an end without a start",
+                input: "chr1:-200",
+                expect_error: false,
+                expected: Region {
+                    name: "chr1".into(),
+                    start: None,
+                    end: Some(200),
+                },
+            },
+            TestCase {
+                name: "Should allow thousands separators",
+                input: "chr1:1,000-2,000",
+                expect_error: false,
+                expected: Region {
+                    name: "chr1".into(),
+                    start: Some(1000),
+                    end: Some(2000),
+                },
+            },
+            TestCase {
+                name: "Should split the name on the last colon",
+                input: "HLA-A*01:01",
+                expect_error: false,
+                expected: Region {
+                    name: "HLA-A*01".into(),
+                    start: Some(1),
+                    end: None,
+                },
+            },
+            TestCase {
+                name: "Should treat a non numeric range as part of the name",
+                input: "chrUn:abc",
+                expect_error: false,
+                expected: Region::whole("chrUn:abc"),
+            },
+            TestCase {
+                name: "Should treat a zero start as part of the name",
+                input: "chr1:0-10",
+                expect_error: false,
+                expected: Region::whole("chr1:0-10"),
+            },
+            TestCase {
+                name: "Should return an error on an empty region",
+                input: "",
+                expect_error: true,
+                expected: Region::whole(""),
+            },
+        ];
+        for test_case in test_cases {
+            let actual = test_case.input.parse::<Region>();
+            if test_case.expect_error {
+                assert!(actual.is_err(), "{}", test_case.name);
+            } else {
+                assert_eq!(Ok(test_case.expected), actual, "{}", test_case.name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_region_interval() {
+        struct TestCase<'a> {
+            name: &'a str,
+            region: &'a str,
+            length: usize,
+            expect_error: bool,
+            expected: (usize, usize),
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should span the whole sequence",
+                region: "chr1",
+                length: 10,
+                expect_error: false,
+                expected: (0, 10),
+            },
+            TestCase {
+                name: "Should convert to a 0-based half open interval",
+                region: "chr1:2-5",
+                length: 10,
+                expect_error: false,
+                expected: (1, 5),
+            },
+            TestCase {
+                name: "Should clamp the end to the sequence length",
+                region: "chr1:5-50",
+                length: 10,
+                expect_error: false,
+                expected: (4, 10),
+            },
+            TestCase {
+                name: "Should return an empty interval past the end of the sequence",
+                region: "chr1:20-30",
+                length: 10,
+                expect_error: false,
+                expected: (10, 10),
+            },
+            TestCase {
+                name: "Should return an empty interval if end is just before start",
+                region: "chr1:3-2",
+                length: 10,
+                expect_error: false,
+                expected: (2, 2),
+            },
+            TestCase {
+                name: "Should return an error if start is after end",
+                region: "chr1:5-2",
+                length: 10,
+                expect_error: true,
+                expected: (0, 0),
+            },
+        ];
+        for test_case in test_cases {
+            let region: Region = test_case.region.parse().unwrap();
+            let actual = region.interval(test_case.length);
+            if test_case.expect_error {
+                assert!(actual.is_err(), "{}", test_case.name);
+            } else {
+                assert_eq!(Ok(test_case.expected), actual, "{}", test_case.name);
+            }
+        }
+
+        let region = Region {
+            name: "chr1".into(),
+            start: Some(0),
+            end: Some(5),
+        };
+        assert_eq!(
+            Ok((0, 5)),
+            region.interval(10),
+            "Should take a start of 0 as 1",
+        );
+        assert_eq!(
+            Ok((10, 10)),
+            Region::from_bed("chr1", 10, 10).interval(20),
+            "Should return an empty interval for an empty BED interval",
+        );
+    }
+
+    #[test]
+    fn test_reader() {
+        let input: &[u8] =
+            b"# comment\ntrack name=test\nchr1:100-200\r\n\nchr2\nchr3\t0\t10\tfeature\n\
+              chr4\t10\t10\n";
+        let actual: Result<Vec<Entry>> = Reader::new(input).collect();
+        assert_eq!(
+            Ok(vec![
+                Entry::Text("chr1:100-200".into()),
+                Entry::Text("chr2".into()),
+                Entry::Bed(Region {
+                    name: "chr3".into(),
+                    start: Some(1),
+                    end: Some(10),
+                }),
+                Entry::Bed(Region::from_bed("chr4", 10, 10)),
+            ]),
+            actual,
+            "Should read samtools and BED-style regions",
+        );
+
+        let input: &[u8] = b"chr1\tten\t20\n";
+        let actual: Result<Vec<Entry>> = Reader::new(input).collect();
+        assert!(
+            actual.is_err(),
+            "Should return an error on an invalid BED line"
+        );
+
+        let input: &[u8] = b"chr1\t20\t10\n";
+        let actual: Result<Vec<Entry>> = Reader::new(input).collect();
+        assert!(
+            actual.is_err(),
+            "Should return an error on a BED line that ends before it starts"
+        );
+
+        let input: &[u8] = b"chr1\t10\n";
+        let actual: Result<Vec<Entry>> = Reader::new(input).collect();
+        assert!(
+            actual.is_err(),
+            "Should return an error on a short BED line"
+        );
+    }
+
+    #[test]
+    fn test_region_display() {
+        for region in ["chr1", "chr1:100-200", "chr1:100"] {
+            assert_eq!(
+                region,
+                region.parse::<Region>().unwrap().to_string(),
+                "Should display a region as it was parsed",
+            );
+        }
+    }
+}
@@ -0,0 +1,243 @@
+/// Complement a base
+///
+/// IUPAC ambiguity codes are complemented to the code matching the complementary set of bases
+/// and the case of the base is preserved.  Any other byte is returned unchanged.
+///
+pub fn complement(base: u8) -> u8 {
+    let complement = match base.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        b'U' => b'A',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        _ => return base,
+    };
+    if base.is_ascii_lowercase() {
+        complement.to_ascii_lowercase()
+    } else {
+        complement
+    }
+}
+
+/// Reverse complement a sequence in place
+pub fn reverse_complement(sequence: &mut [u8]) {
+    sequence.reverse();
+    for base in sequence.iter_mut() {
+        *base = complement(*base);
+    }
+}
+
+/// Md5 computes the checksum of a sequence as used by `@SQ M5` tags
+///
+/// Bases are upper-cased and whitespace is skipped before hashing, so the checksum doesn't
+/// depend on line lengths or soft masking.
+///
+pub struct Md5 {
+    context: md5::Context,
+    buffer: Vec<u8>,
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Md5 {
+    /// Construct a new checksum
+    pub fn new() -> Self {
+        Self {
+            context: md5::Context::new(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Add bases to the checksum
+    pub fn update(&mut self, bases: &[u8]) {
+        self.buffer.clear();
+        self.buffer.extend(
+            bases
+                .iter()
+                .filter(|base| !base.is_ascii_whitespace())
+                .map(|base| base.to_ascii_uppercase()),
+        );
+        self.context.consume(&self.buffer);
+    }
+
+    /// Consume the checksum and return it as a lowercase hex string
+    pub fn finalize(self) -> String {
+        format!("{:x}", self.context.compute())
+    }
+}
+
+/// NameSplit is the rule for splitting a description line into a sequence name and a description
+///
+/// The line is taken without its `>` or `@` prefix.  Leading and trailing whitespace is removed
+/// from both parts, and an empty description is `None`.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum NameSplit {
+    /// The name ends at the first space or tab, as in samtools
+    #[default]
+    Whitespace,
+    /// The name is the whole line, with no description
+    Line,
+    /// The name ends at the first occurrence of a delimiter
+    Delimiter(char),
+}
+
+impl NameSplit {
+    /// Split a description line into a sequence name and an optional description
+    pub fn split<'a>(&self, line: &'a str) -> (&'a str, Option<&'a str>) {
+        let line = line.trim();
+        let split = match self {
+            Self::Whitespace => line.split_once([' ', '\t']),
+            Self::Line => None,
+            Self::Delimiter(delimiter) => line.split_once(*delimiter),
+        };
+        match split {
+            Some((name, description)) => {
+                let description = description.trim();
+                (name.trim(), Some(description).filter(|d| !d.is_empty()))
+            }
+            None => (line, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complement() {
+        struct TestCase<'a> {
+            name: &'a str,
+            bases: &'a [u8],
+            expected: &'a [u8],
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should complement nucleotides",
+                bases: b"ACGTU",
+                expected: b"TGCAA",
+            },
+            TestCase {
+                name: "Should complement ambiguity codes",
+                bases: b"RYKMBVDHSWN",
+                expected: b"YRMKVBHDSWN",
+            },
+            TestCase {
+                name: "Should preserve case",
+                bases: b"acgtRy",
+                expected: b"tgcaYr",
+            },
+            TestCase {
+                name: "Should leave other bytes unchanged",
+                bases: b"-*.",
+                expected: b"-*.",
+            },
+        ];
+        for test_case in test_cases {
+            let actual: Vec<u8> = test_case.bases.iter().map(|&b| complement(b)).collect();
+            assert_eq!(test_case.expected.to_vec(), actual, "{}", test_case.name);
+        }
+    }
+
+    #[test]
+    fn test_md5() {
+        let mut md5 = Md5::new();
+        md5.update(b"acgt\r\n");
+        md5.update(b"NNAC\n");
+        let mut expected = Md5::new();
+        expected.update(b"ACGTNNAC");
+        assert_eq!(
+            expected.finalize(),
+            md5.finalize(),
+            "Should normalize case and whitespace",
+        );
+
+        let mut md5 = Md5::new();
+        md5.update(b"ACGT");
+        assert_eq!(
+            "f1f8f4bf413b16ad135722aa4591043e",
+            md5.finalize(),
+            "Should compute the MD5 of the bases",
+        );
+    }
+
+    #[test]
+    fn test_reverse_complement() {
+        let mut sequence = b"AACGTn".to_vec();
+        reverse_complement(&mut sequence);
+        assert_eq!(
+            b"nACGTT".to_vec(),
+            sequence,
+            "Should reverse and complement a sequence",
+        );
+    }
+
+    #[test]
+    fn test_name_split() {
+        struct TestCase<'a> {
+            name: &'a str,
+            name_split: NameSplit,
+            line: &'a str,
+            expected: (&'a str, Option<&'a str>),
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should split the name from the description",
+                name_split: NameSplit::Whitespace,
+                line: "chr1 AC:CM000663.2 gi:568336023\n",
+                expected: ("chr1", Some("AC:CM000663.2 gi:568336023")),
+            },
+            TestCase {
+                name: "Should split on tabs",
+                name_split: NameSplit::Whitespace,
+                line: "chr1\tdescription",
+                expected: ("chr1", Some("description")),
+            },
+            TestCase {
+                name: "Should remove leading spaces and trailing new lines",
+                name_split: NameSplit::Whitespace,
+                line: "  abc  \r\n",
+                expected: ("abc", None),
+            },
+            TestCase {
+                name: "Should use the full line",
+                name_split: NameSplit::Line,
+                line: "chr1 description\n",
+                expected: ("chr1 description", None),
+            },
+            TestCase {
+                name: "Should split on a custom delimiter",
+                name_split: NameSplit::Delimiter('|'),
+                line: "gi|568336023|chr1\n",
+                expected: ("gi", Some("568336023|chr1")),
+            },
+            TestCase {
+                name: "Should use the full line without the delimiter",
+                name_split: NameSplit::Delimiter('|'),
+                line: "chr1 description\n",
+                expected: ("chr1 description", None),
+            },
+        ];
+        for test_case in test_cases {
+            assert_eq!(
+                test_case.expected,
+                test_case.name_split.split(test_case.line),
+                "{}",
+                test_case.name
+            );
+        }
+    }
+}
@@ -0,0 +1,261 @@
+use super::bgzf;
+use super::compression::{Compression, SNIFF_LEN};
+use crate::errors::Result;
+use std::io::{Read, Seek, SeekFrom};
+
+const GZI_SUFFIX: &str = ".gzi";
+
+/// Access is how an uncompressed file is read
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Access {
+    /// Read with system calls
+    #[default]
+    Read,
+    /// Map the file into memory, which avoids a system call per read or seek
+    Mmap,
+}
+
+/// Source is a FASTA/FASTQ input file that may be BGZF compressed
+///
+/// Offsets are always in the uncompressed data.
+///
+pub enum Source {
+    /// Uncompressed file
+    Plain(std::fs::File),
+    /// Uncompressed memory mapped file
+    Mmap(std::io::Cursor<Mapped>),
+    /// BGZF compressed file
+    Bgzf(bgzf::Reader<std::fs::File>),
+}
+
+impl Source {
+    /// Open a file, detecting BGZF compression
+    ///
+    /// If a BGZF file has a `.gzi` index next to it, the index is used for seeking.  An error is
+    /// returned for compression formats that don't support random access.
+    ///
+    pub fn from_path<P: AsRef<std::path::Path>>(path: P) -> Result<Self> {
+        Self::from_path_with(path, Access::default())
+    }
+
+    /// Open a file, reading uncompressed data with `access`
+    ///
+    /// BGZF compressed data is always read with system calls.  A memory mapped file must not be
+    /// modified while it is open.
+    ///
+    pub fn from_path_with<P: AsRef<std::path::Path>>(path: P, access: Access) -> Result<Self> {
+        Self::open(path.as_ref(), access, true).map_err(|e| e.with_path(path))
+    }
+
+    /// Open a file like [`Source::from_path_with`], ignoring any `.gzi` index
+    ///
+    /// The BGZF block index is built from the blocks that are read, so an index that no longer
+    /// matches the file is not carried over when it is indexed again.
+    ///
+    pub fn from_path_unindexed<P: AsRef<std::path::Path>>(path: P, access: Access) -> Result<Self> {
+        Self::open(path.as_ref(), access, false).map_err(|e| e.with_path(path))
+    }
+
+    /// Open a file without locating errors, loading its `.gzi` index if `gzi` is set
+    fn open(path: &std::path::Path, access: Access, gzi: bool) -> Result<Self> {
+        let mut file = std::fs::File::open(path)?;
+        let mut header = Vec::new();
+        (&mut file)
+            .take(SNIFF_LEN as u64)
+            .read_to_end(&mut header)?;
+        file.seek(SeekFrom::Start(0))?;
+        let compression = Compression::detect(&header);
+        compression.check_random_access()?;
+        match (compression, access) {
+            (Compression::None, Access::Read) => return Ok(Self::Plain(file)),
+            (Compression::None, Access::Mmap) => {
+                // SAFETY: the map is only read, and callers must not modify the file while it is
+                // open, as documented on `from_path_with`.
+                let map = unsafe { memmap2::Mmap::map(&file)? };
+                return Ok(Self::Mmap(std::io::Cursor::new(Mapped::new(map))));
+            }
+            _ => {}
+        }
+        let gzi_path = gzi_path(path);
+        if !gzi || !gzi_path.exists() {
+            return Ok(Self::Bgzf(bgzf::Reader::new(file)));
+        }
+        let index = std::fs::File::open(&gzi_path)
+            .map_err(|e| e.into())
+            .and_then(|file| bgzf::GziIndex::read(std::io::BufReader::new(file)))
+            .map_err(|e| e.with_path(&gzi_path))?;
+        Ok(Self::Bgzf(bgzf::Reader::with_index(file, index)))
+    }
+
+    /// Get the memory mapped data, or `None` if the source is read with system calls
+    pub fn mapped(&self) -> Option<&Mapped> {
+        match self {
+            Self::Mmap(reader) => Some(reader.get_ref()),
+            Self::Plain(_) | Self::Bgzf(_) => None,
+        }
+    }
+
+    /// Get the BGZF block index, or `None` if the source is not compressed
+    pub fn gzi_index(&mut self) -> Result<Option<&bgzf::GziIndex>> {
+        match self {
+            Self::Plain(_) | Self::Mmap(_) => Ok(None),
+            Self::Bgzf(reader) => Ok(Some(reader.index()?)),
+        }
+    }
+}
+
+impl Read for Source {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Self::Plain(reader) => reader.read(buf),
+            Self::Mmap(reader) => reader.read(buf),
+            Self::Bgzf(reader) => reader.read(buf),
+        }
+    }
+}
+
+impl Seek for Source {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Self::Plain(reader) => reader.seek(pos),
+            Self::Mmap(reader) => reader.seek(pos),
+            Self::Bgzf(reader) => reader.seek(pos),
+        }
+    }
+}
+
+/// Mapped is a range of a memory mapped file
+///
+/// Clones share the same mapping, so ranges of one file can be handed to several readers without
+/// copying.
+///
+#[derive(Clone)]
+pub struct Mapped {
+    map: std::sync::Arc<memmap2::Mmap>,
+    start: usize,
+    end: usize,
+}
+
+impl Mapped {
+    /// Construct from a whole mapping
+    fn new(map: memmap2::Mmap) -> Self {
+        let end = map.len();
+        Self {
+            map: std::sync::Arc::new(map),
+            start: 0,
+            end,
+        }
+    }
+
+    /// Get the bytes `[start, end)` of this range, sharing the mapping
+    ///
+    /// # Panics
+    ///
+    /// Panics if `start > end` or `end` is past the end of this range.
+    ///
+    pub fn slice(&self, start: usize, end: usize) -> Self {
+        assert!(start <= end && end <= self.len(), "range out of bounds");
+        Self {
+            map: self.map.clone(),
+            start: self.start + start,
+            end: self.start + end,
+        }
+    }
+
+    /// Number of bytes in the range
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Whether the range is empty
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl AsRef<[u8]> for Mapped {
+    fn as_ref(&self) -> &[u8] {
+        &self.map[self.start..self.end]
+    }
+}
+
+/// Path of the `.gzi` index next to a BGZF file
+pub fn gzi_path<P: AsRef<std::path::Path>>(path: P) -> std::path::PathBuf {
+    let mut gzi_path = path.as_ref().as_os_str().to_owned();
+    gzi_path.push(GZI_SUFFIX);
+    gzi_path.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_access() {
+        struct TestCase<'a> {
+            name: &'a str,
+            file: &'a str,
+            input: &'a [u8],
+            seek: u64,
+        }
+        let test_cases = [
+            TestCase {
+                name: "Should map an empty file",
+                file: "empty",
+                input: b"",
+                seek: 0,
+            },
+            TestCase {
+                name: "Should read and seek a mapped file",
+                file: "fasta",
+                input: b">a\nACGT\n>b\nAC\n",
+                seek: 9,
+            },
+            TestCase {
+                name: "Should seek past the end of a mapped file",
+                file: "past-end",
+                input: b">a\nACGT\n",
+                seek: 20,
+            },
+        ];
+        for test_case in test_cases {
+            let path = std::env::temp_dir().join(format!(
+                "rust-samtools-{}-source-{}.fa",
+                std::process::id(),
+                test_case.file
+            ));
+            std::fs::write(&path, test_case.input).unwrap();
+            let read = |access| -> Result<(Vec<u8>, Vec<u8>)> {
+                let mut source = Source::from_path_with(&path, access)?;
+                let mut all = Vec::new();
+                source.read_to_end(&mut all)?;
+                source.seek(SeekFrom::Start(test_case.seek))?;
+                let mut rest = Vec::new();
+                source.read_to_end(&mut rest)?;
+                Ok((all, rest))
+            };
+            let expected = read(Access::Read);
+            assert!(expected.is_ok(), "{}", test_case.name);
+            assert_eq!(expected, read(Access::Mmap), "{}", test_case.name);
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_mapped_slice() {
+        let path = std::env::temp_dir().join(format!(
+            "rust-samtools-{}-source-slice.fa",
+            std::process::id()
+        ));
+        std::fs::write(&path, b">a\nACGT\n").unwrap();
+        let source = Source::from_path_with(&path, Access::Mmap).unwrap();
+        let mapped = source.mapped().unwrap().slice(3, 8);
+        assert_eq!(b"ACGT\n", mapped.as_ref(), "Should slice the mapping");
+        assert_eq!(
+            b"CG",
+            mapped.slice(1, 3).as_ref(),
+            "Should slice relative to the range",
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
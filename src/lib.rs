@@ -0,0 +1,25 @@
+//! Rust implementation of [`samtools`]
+//!
+//! Besides the `rust-samtools` command line, the crate can be used as a library.  Formats are
+//! read and written through the modules in [`io`], and all errors are reported as
+//! [`errors::Error`].
+//!
+//! # Examples
+//!
+//! Index a FASTA file and fetch a region from it:
+//!
+//! ```
+//! use rust_samtools::io::fai;
+//!
+//! let fasta: &[u8] = b">chr1\nACGTACGT\nACGT\n";
+//! let records = fai::Indexer::new(std::io::Cursor::new(fasta), fai::IndexerFormat::FASTA)
+//!     .iter()
+//!     .collect::<rust_samtools::errors::Result<Vec<_>>>()?;
+//! let mut reader = fai::IndexedReader::new(std::io::Cursor::new(fasta), records);
+//! assert_eq!(b"TACGTA".to_vec(), reader.fetch("chr1", 3, 9)?);
+//! # Ok::<(), rust_samtools::errors::Error>(())
+//! ```
+//!
+//! [`samtools`]: https://www.htslib.org/
+pub mod errors;
+pub mod io;
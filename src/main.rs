@@ -2,13 +2,12 @@
 //!
 //! [`samtools`]: https://www.htslib.org/
 mod cmd;
-mod errors;
-mod io;
 
-extern crate clap;
-extern crate csv;
-extern crate serde;
+use rust_samtools::{errors, io};
 
-fn main() -> errors::Result<()> {
-    cmd::run()
+fn main() {
+    if let Err(e) = cmd::run() {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
 }